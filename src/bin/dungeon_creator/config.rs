@@ -0,0 +1,273 @@
+use std::{fs, path::Path, path::PathBuf, rc::Rc};
+
+use dungeon_creator::{
+    dungeon::layout::DungeonLayoutConfig,
+    error::GenerationError,
+    floor::floor_architecture::FloorRoom,
+    room::{
+        automata::AutomataRoomBuilder,
+        bsp::BspRoomBuilder,
+        door::{DoorConfig, DoorRoomBuilder},
+        drunkard::DrunkardRoomBuilder,
+        grid::GridRoomBuilder,
+        hazard::{HazardConfig, HazardRoomBuilder},
+        maze::MazeRoomBuilder,
+        prefab::PrefabRoomBuilder,
+        rectangles::RectanglesRoomBuilder,
+        room::DungeonRoom,
+        room_builder::RoomBuilder,
+        wfc::WfcRoomBuilder,
+    },
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Settings for generating a dungeon, read from a RON or JSON file.
+/// Every setting is optional; missing settings keep their default values.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorConfig {
+    pub layout: DungeonLayoutConfig,
+    /// Room builders to choose from randomly for every room
+    pub builders: Vec<BuilderConfig>,
+    /// Rivers and pools painted onto the rooms of all room builders
+    pub hazards: Option<HazardConfig>,
+    /// Doors placed where corridors enter chambers, in the rooms of all room builders
+    pub doors: Option<DoorConfig>,
+    /// Turns the exits of all rooms into doors
+    pub exit_doors: bool,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            layout: DungeonLayoutConfig::default(),
+            builders: vec![BuilderConfig::Automata(AutomataRoomBuilder::default())],
+            hazards: None,
+            doors: None,
+            exit_doors: false,
+        }
+    }
+}
+
+/// A room builder with its parameters
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuilderConfig {
+    Automata(AutomataRoomBuilder),
+    Bsp(BspRoomBuilder),
+    Drunkard(DrunkardRoomBuilder),
+    Grid(GridRoomBuilder),
+    Maze(MazeRoomBuilder),
+    Rectangles(RectanglesRoomBuilder),
+    /// Wave function collapse, learning from a sample room file in the format of print_room
+    Wfc {
+        rows: usize,
+        cols: usize,
+        sample: PathBuf,
+    },
+    /// Prefab rooms loaded from a directory, with a builder for rooms no prefab fits
+    Prefab {
+        dir: PathBuf,
+        fallback: Box<BuilderConfig>,
+    },
+}
+
+impl GeneratorConfig {
+    /// Reads a config file; files with a "json" extension are read as JSON, all others as RON
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("unable to read config {}: {}", path.display(), e))?;
+
+        let config = if path.extension().map(|e| e == "json").unwrap_or(false) {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            ron::from_str(&content).map_err(|e| e.to_string())
+        };
+
+        config.map_err(|e| format!("invalid config {}: {}", path.display(), e))
+    }
+
+    /// Creates the configured room builders, loading the files they need
+    pub fn room_builders(&self) -> Result<Vec<Rc<dyn RoomBuilder>>, String> {
+        if self.builders.is_empty() {
+            return Err("the config needs at least one room builder".to_string());
+        }
+
+        self.builders
+            .iter()
+            .map(|b| {
+                let mut builder = b.room_builder()?;
+                if let Some(hazards) = &self.hazards {
+                    builder = Rc::new(HazardRoomBuilder::new(
+                        Box::new(SharedRoomBuilder(builder)),
+                        hazards.clone(),
+                    ));
+                }
+                if let Some(doors) = &self.doors {
+                    builder = Rc::new(DoorRoomBuilder::new(
+                        Box::new(SharedRoomBuilder(builder)),
+                        doors.clone(),
+                    ));
+                }
+
+                Ok(builder)
+            })
+            .collect()
+    }
+}
+
+impl BuilderConfig {
+    fn room_builder(&self) -> Result<Rc<dyn RoomBuilder>, String> {
+        let builder: Rc<dyn RoomBuilder> = match self {
+            Self::Automata(b) => Rc::new(b.clone()),
+            Self::Bsp(b) => Rc::new(b.clone()),
+            Self::Drunkard(b) => Rc::new(b.clone()),
+            Self::Grid(b) => Rc::new(b.clone()),
+            Self::Maze(b) => Rc::new(b.clone()),
+            Self::Rectangles(b) => Rc::new(b.clone()),
+            Self::Wfc { rows, cols, sample } => {
+                let content = fs::read_to_string(sample).map_err(|e| {
+                    format!("unable to read wfc sample {}: {}", sample.display(), e)
+                })?;
+                let builder = WfcRoomBuilder::new(*rows, *cols, &content.replace("\r\n", "\n"))
+                    .ok_or_else(|| format!("invalid wfc sample {}", sample.display()))?;
+                Rc::new(builder)
+            }
+            Self::Prefab { dir, fallback } => {
+                let fallback = Box::new(SharedRoomBuilder(fallback.room_builder()?));
+                let builder = PrefabRoomBuilder::load_dir(dir, fallback)
+                    .map_err(|e| format!("unable to load prefabs {}: {}", dir.display(), e))?;
+                Rc::new(builder)
+            }
+        };
+
+        Ok(builder)
+    }
+}
+
+/// A room builder shared between floors, since every floor takes its own boxed room builders
+pub struct SharedRoomBuilder(pub Rc<dyn RoomBuilder>);
+
+impl RoomBuilder for SharedRoomBuilder {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.0.create_room(rng, room_config)
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        self.0.try_create_room(rng, room_config)
+    }
+
+    fn get_rows(&self) -> usize {
+        self.0.get_rows()
+    }
+
+    fn get_cols(&self) -> usize {
+        self.0.get_cols()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dungeon_creator::room::hazard::Hazard;
+
+    use super::*;
+
+    #[test]
+    fn parses_ron_config_with_defaults() {
+        let input = r#"(
+            layout: (floor_size: (start: 2, end: 3)),
+            builders: [
+                (type: "maze", loop_percent: 20),
+                (type: "automata"),
+            ],
+        )"#;
+
+        let result: GeneratorConfig = ron::from_str(input).unwrap();
+
+        assert_eq!(2..3, result.layout.floor_size);
+        assert_eq!(0..2, result.layout.floors_above);
+        match &result.builders[0] {
+            BuilderConfig::Maze(maze) => {
+                assert_eq!(20, maze.loop_percent);
+                assert_eq!(15, maze.rows);
+            }
+            _ => panic!("expected maze builder"),
+        }
+        assert_eq!(2, result.room_builders().unwrap().len());
+    }
+
+    #[test]
+    fn parses_json_config() {
+        let input = r#"{
+            "builders": [
+                { "type": "rectangles", "rows": 20, "granularity": "Large" },
+                { "type": "prefab", "dir": "resources/prefabs", "fallback": { "type": "bsp" } }
+            ]
+        }"#;
+
+        let result: GeneratorConfig = serde_json::from_str(input).unwrap();
+
+        assert_eq!(2, result.builders.len());
+        assert_eq!(3..5, result.layout.floor_size);
+    }
+
+    #[test]
+    fn parses_door_config() {
+        let input = r#"(
+            doors: Some((secret_percent: 10)),
+            exit_doors: true,
+        )"#;
+
+        let result: GeneratorConfig = ron::from_str(input).unwrap();
+
+        let doors = result.doors.clone().unwrap();
+        assert_eq!(100, doors.door_percent);
+        assert_eq!(10, doors.secret_percent);
+        assert!(result.exit_doors);
+        assert_eq!(1, result.room_builders().unwrap().len());
+    }
+
+    #[test]
+    fn parses_hazard_config() {
+        let input = r#"(
+            hazards: Some((hazard: Lava, rivers: 2)),
+        )"#;
+
+        let result: GeneratorConfig = ron::from_str(input).unwrap();
+
+        let hazards = result.hazards.clone().unwrap();
+        assert_eq!(Hazard::Lava, hazards.hazard);
+        assert_eq!(2, hazards.rivers);
+        assert_eq!(40, hazards.pool_percent);
+        assert_eq!(1, result.room_builders().unwrap().len());
+    }
+
+    #[test]
+    fn does_not_create_builders_without_any_configured() {
+        let config = GeneratorConfig {
+            builders: vec![],
+            ..Default::default()
+        };
+
+        assert!(config.room_builders().is_err());
+    }
+
+    #[test]
+    fn fails_to_create_builder_with_missing_files() {
+        let config = GeneratorConfig {
+            builders: vec![BuilderConfig::Wfc {
+                rows: 10,
+                cols: 10,
+                sample: PathBuf::from("does/not/exist.txt"),
+            }],
+            ..Default::default()
+        };
+
+        assert!(config.room_builders().is_err());
+    }
+}
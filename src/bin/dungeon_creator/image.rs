@@ -0,0 +1,142 @@
+use dungeon_creator::{
+    dungeon::{room::ArrangedDungeonRoom, stitch::stitch_floor},
+    room::tile::DungeonTile,
+};
+
+const BACKGROUND: [u8; 3] = [0, 0, 0];
+
+/// An RGB image of the dungeon, with every tile drawn as a square
+pub struct DungeonImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl DungeonImage {
+    /// Draws the stitched floors of the dungeon from the lowest to the highest floor, separated by an empty row of tiles
+    pub fn draw(rooms: &[ArrangedDungeonRoom], tile_size: u32) -> Self {
+        let floors: Vec<i32> = match (
+            rooms.iter().map(|r| r.dungeon_coords.floor).min(),
+            rooms.iter().map(|r| r.dungeon_coords.floor).max(),
+        ) {
+            (Some(min), Some(max)) => (min..=max).collect(),
+            _ => vec![],
+        };
+
+        let maps: Vec<_> = floors
+            .iter()
+            .map(|floor| {
+                stitch_floor(
+                    rooms
+                        .iter()
+                        .filter(|r| r.dungeon_coords.floor == *floor)
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let columns = maps.iter().map(|m| m.columns).max().unwrap_or(0);
+        let rows = maps
+            .iter()
+            .map(|m| m.rows + 1)
+            .sum::<usize>()
+            .saturating_sub(1);
+        let width = columns as u32 * tile_size;
+        let height = rows as u32 * tile_size;
+        let mut image = Self {
+            width,
+            height,
+            pixels: BACKGROUND.repeat((width * height) as usize),
+        };
+
+        let mut top = 0;
+        for map in maps.iter() {
+            for (idx, tile) in map.tiles.iter().enumerate() {
+                let row = top + idx / map.columns;
+                let col = idx % map.columns;
+                image.fill_tile(row as u32, col as u32, tile_size, tile_color(tile));
+            }
+            top += map.rows + 1;
+        }
+
+        image
+    }
+
+    /// Encodes the image as PNG
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut output = vec![];
+        let mut encoder = png::Encoder::new(&mut output, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| format!("unable to encode image: {}", e))?;
+
+        Ok(output)
+    }
+
+    fn fill_tile(&mut self, row: u32, col: u32, tile_size: u32, color: [u8; 3]) {
+        for y in row * tile_size..(row + 1) * tile_size {
+            for x in col * tile_size..(col + 1) * tile_size {
+                let offset = ((y * self.width + x) * 3) as usize;
+                self.pixels[offset..offset + 3].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+fn tile_color(tile: &DungeonTile) -> [u8; 3] {
+    match tile {
+        DungeonTile::Floor => [200, 200, 190],
+        DungeonTile::Wall => [60, 60, 70],
+        DungeonTile::Exit => [90, 150, 240],
+        DungeonTile::StairsUp => [80, 200, 90],
+        DungeonTile::StairsDown => [220, 90, 60],
+        DungeonTile::Door => [160, 110, 60],
+        DungeonTile::LockedDoor => [150, 60, 160],
+        DungeonTile::SecretDoor => [90, 80, 80],
+        DungeonTile::Key => [240, 210, 60],
+        DungeonTile::Water => [50, 110, 200],
+        DungeonTile::Lava => [230, 100, 20],
+        DungeonTile::Chasm => [20, 20, 25],
+        DungeonTile::Bridge => [140, 100, 60],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dungeon_creator::{dungeon::coords::DungeonCoordinates, room::print::parse_room};
+
+    use super::*;
+
+    #[test]
+    fn draws_floors_below_each_other() {
+        let rooms = vec![create_room(0, "..\n.."), create_room(1, "...")];
+
+        let result = DungeonImage::draw(&rooms, 2);
+
+        assert_eq!(6, result.width);
+        // 2 rows for floor 0, 1 empty row, 1 row for floor 1
+        assert_eq!(8, result.height);
+        assert_eq!(6 * 8 * 3, result.pixels.len());
+        assert_eq!(&tile_color(&DungeonTile::Floor), &result.pixels[0..3]);
+        assert_eq!(&BACKGROUND, &result.pixels[(4 * 6 * 3)..(4 * 6 * 3 + 3)]);
+    }
+
+    #[test]
+    fn encodes_png() {
+        let rooms = vec![create_room(0, ".#")];
+
+        let result = DungeonImage::draw(&rooms, 1).to_png().unwrap();
+
+        assert_eq!(&[0x89, b'P', b'N', b'G'], &result[0..4]);
+    }
+
+    fn create_room(floor: i32, tiles: &str) -> ArrangedDungeonRoom {
+        let room = parse_room(tiles).unwrap();
+        let mut arranged = ArrangedDungeonRoom::from(&room);
+        arranged.dungeon_coords = DungeonCoordinates::new(floor, 0, 0);
+        arranged
+    }
+}
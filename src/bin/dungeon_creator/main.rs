@@ -0,0 +1,282 @@
+mod config;
+mod image;
+
+use std::{
+    fs,
+    io::{self, Write},
+    ops::Range,
+    path::PathBuf,
+    process::ExitCode,
+    rc::Rc,
+};
+
+use clap::{Parser, ValueEnum};
+use config::{GeneratorConfig, SharedRoomBuilder};
+use dungeon_creator::{
+    dungeon::{
+        dungeon_builder::DungeonBuilder,
+        layout::{DungeonLayout, DungeonLayoutConfig},
+        print::print_dungeon,
+        progression::place_locks_and_keys,
+        room::ArrangedDungeonRoom,
+        save::DungeonSave,
+    },
+    error::GenerationError,
+    floor::floor_architecture::FloorLayout,
+    room::room_builder::RoomBuilder,
+};
+use image::DungeonImage;
+
+/// Generates a dungeon from a seed and a config.
+/// The same seed and config always create the same dungeon.
+#[derive(Parser)]
+#[command(name = "dungeon_creator")]
+struct Args {
+    /// Seed of the random number generator
+    #[arg(short, long, default_value_t = 0)]
+    seed: u64,
+
+    /// Config file in RON format, or JSON with a "json" extension; uses the default config if omitted
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Ascii)]
+    format: Format,
+
+    /// File to write the dungeon to; writes to the standard output if omitted
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Size of a tile in pixels, for the png format
+    #[arg(long, default_value_t = 4)]
+    tile_size: u32,
+
+    /// Overrides the range of floors above the first floor of the config, e.g. "0..2"
+    #[arg(long, value_parser = parse_range)]
+    floors_above: Option<Range<u8>>,
+
+    /// Overrides the range of floors below the first floor of the config, e.g. "0..2"
+    #[arg(long, value_parser = parse_range)]
+    floors_below: Option<Range<u8>>,
+
+    /// Overrides the range of rooms per floor of the config, e.g. "3..5"
+    #[arg(long, value_parser = parse_range)]
+    floor_size: Option<Range<u8>>,
+
+    /// Prints the config in RON format instead of generating a dungeon, as a starting point for config files
+    #[arg(long)]
+    print_config: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Floors printed as text, like print_dungeon
+    Ascii,
+    /// Save document with the layout and all rooms
+    Json,
+    /// Save document with the layout and all rooms
+    Ron,
+    /// Image of all floors, stitched together
+    Png,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut config = match &args.config {
+        Some(path) => GeneratorConfig::load(path)?,
+        None => GeneratorConfig::default(),
+    };
+    if let Some(range) = args.floors_above.clone() {
+        config.layout.floors_above = range;
+    }
+    if let Some(range) = args.floors_below.clone() {
+        config.layout.floors_below = range;
+    }
+    if let Some(range) = args.floor_size.clone() {
+        config.layout.floor_size = range;
+    }
+
+    if args.print_config {
+        let output = ron::ser::to_string_pretty(&config, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        return write_output(&args.output, format!("{}\n", output).as_bytes());
+    }
+
+    let builder = ConfiguredDungeonBuilder {
+        layout_config: config.layout.clone(),
+        room_builders: config.room_builders()?,
+        exit_doors: config.exit_doors,
+    };
+    let (layout, rooms) = builder
+        .create_dungeon(args.seed)
+        .map_err(|e| e.to_string())?;
+
+    let output = match args.format {
+        Format::Ascii => print_dungeon(rooms.iter().collect()).into_bytes(),
+        Format::Json => serde_json::to_string_pretty(&DungeonSave::new(layout, rooms))
+            .map_err(|e| e.to_string())?
+            .into_bytes(),
+        Format::Ron => ron::ser::to_string_pretty(
+            &DungeonSave::new(layout, rooms),
+            ron::ser::PrettyConfig::default(),
+        )
+        .map_err(|e| e.to_string())?
+        .into_bytes(),
+        Format::Png => DungeonImage::draw(&rooms, args.tile_size.max(1)).to_png()?,
+    };
+
+    write_output(&args.output, &output)
+}
+
+fn write_output(path: &Option<PathBuf>, output: &[u8]) -> Result<(), String> {
+    match path {
+        Some(path) => fs::write(path, output)
+            .map_err(|e| format!("unable to write {}: {}", path.display(), e)),
+        None => io::stdout()
+            .write_all(output)
+            .map_err(|e| format!("unable to write output: {}", e)),
+    }
+}
+
+// Parses ranges like "3..5"
+fn parse_range(value: &str) -> Result<Range<u8>, String> {
+    let (start, end) = value
+        .split_once("..")
+        .ok_or_else(|| format!("expected a range like 3..5, found {}", value))?;
+    let start: u8 = start.trim().parse().map_err(|_| "invalid range start")?;
+    let end: u8 = end.trim().parse().map_err(|_| "invalid range end")?;
+    if start >= end {
+        return Err(format!("range {} is empty", value));
+    }
+
+    Ok(start..end)
+}
+
+/// Dungeon builder choosing randomly among the configured room builders for every room
+struct ConfiguredDungeonBuilder {
+    layout_config: DungeonLayoutConfig,
+    room_builders: Vec<Rc<dyn RoomBuilder>>,
+    exit_doors: bool,
+}
+
+impl ConfiguredDungeonBuilder {
+    fn create_dungeon(
+        &self,
+        seed: u64,
+    ) -> Result<(DungeonLayout, Vec<ArrangedDungeonRoom>), GenerationError> {
+        let layout = self.try_seeded_layout(seed)?;
+
+        let mut rooms = vec![];
+        for floor in layout.floors.iter() {
+            rooms.append(&mut self.try_create_dungeon_floor(floor)?);
+        }
+        if self.exit_doors {
+            rooms.iter_mut().for_each(|r| r.set_exit_doors());
+        }
+        place_locks_and_keys(&mut rooms, &layout.progression)?;
+
+        Ok((layout, rooms))
+    }
+
+    fn shared_room_builders(&self) -> Vec<Box<dyn RoomBuilder>> {
+        self.room_builders
+            .iter()
+            .map(|b| Box::new(SharedRoomBuilder(b.clone())) as Box<dyn RoomBuilder>)
+            .collect()
+    }
+}
+
+impl DungeonBuilder for ConfiguredDungeonBuilder {
+    fn create_dungeon_floor(&self, floor_layout: &FloorLayout) -> Vec<ArrangedDungeonRoom> {
+        self.create_rooms(self.shared_room_builders(), floor_layout)
+    }
+
+    fn try_create_dungeon_floor(
+        &self,
+        floor_layout: &FloorLayout,
+    ) -> Result<Vec<ArrangedDungeonRoom>, GenerationError> {
+        self.try_create_rooms(self.shared_room_builders(), floor_layout)
+    }
+
+    fn get_layout_config(&self) -> DungeonLayoutConfig {
+        self.layout_config.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dungeon_creator::room::tile::DungeonTile;
+
+    use super::*;
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(Ok(3..5), parse_range("3..5"));
+        assert!(parse_range("5..3").is_err());
+        assert!(parse_range("3").is_err());
+    }
+
+    #[test]
+    fn creates_same_dungeon_from_same_seed() {
+        let builder = ConfiguredDungeonBuilder {
+            layout_config: DungeonLayoutConfig::default(),
+            room_builders: GeneratorConfig::default().room_builders().unwrap(),
+            exit_doors: false,
+        };
+
+        let (_, first) = builder.create_dungeon(3).unwrap();
+        let (_, second) = builder.create_dungeon(3).unwrap();
+
+        assert!(!first.is_empty());
+        assert!(first == second);
+    }
+
+    #[test]
+    fn turns_exits_into_doors() {
+        let builder = ConfiguredDungeonBuilder {
+            layout_config: DungeonLayoutConfig::default(),
+            room_builders: GeneratorConfig::default().room_builders().unwrap(),
+            exit_doors: true,
+        };
+
+        let (_, rooms) = builder.create_dungeon(3).unwrap();
+
+        let tiles: Vec<&DungeonTile> = rooms.iter().flat_map(|r| r.tiles.iter()).collect();
+        assert!(!tiles.contains(&&DungeonTile::Exit));
+        assert!(tiles.contains(&&DungeonTile::Door));
+    }
+
+    #[test]
+    fn places_locks_and_keys_of_layout() {
+        let mut layout_config = DungeonLayoutConfig::default();
+        layout_config.progression.locks = 2;
+        let builder = ConfiguredDungeonBuilder {
+            layout_config,
+            room_builders: GeneratorConfig::default().room_builders().unwrap(),
+            exit_doors: false,
+        };
+
+        let (layout, rooms) = builder.create_dungeon(3).unwrap();
+
+        let count = |tile| {
+            rooms
+                .iter()
+                .flat_map(|r| r.tiles.iter())
+                .filter(|t| **t == tile)
+                .count()
+        };
+        assert_eq!(layout.progression.keys.len(), count(DungeonTile::Key));
+        assert_eq!(2, layout.progression.locks.len());
+        assert!(count(DungeonTile::LockedDoor) > 0);
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction3D {
     Top,
     Bottom,
@@ -23,6 +24,35 @@ impl Direction3D {
             Self::None => Self::None,
         }
     }
+
+    /// Direction after turning a room by 90 degrees clockwise; up and down are not affected
+    pub fn rotate_clockwise(&self) -> Direction3D {
+        match self {
+            Self::Top => Self::Right,
+            Self::Right => Self::Bottom,
+            Self::Bottom => Self::Left,
+            Self::Left => Self::Top,
+            _ => *self,
+        }
+    }
+
+    /// Direction after mirroring a room along its vertical axis, which swaps left and right
+    pub fn mirror_horizontally(&self) -> Direction3D {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            _ => *self,
+        }
+    }
+
+    /// Direction after mirroring a room along its horizontal axis, which swaps top and bottom
+    pub fn mirror_vertically(&self) -> Direction3D {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Bottom => Self::Top,
+            _ => *self,
+        }
+    }
 }
 
 impl fmt::Display for Direction3D {
@@ -44,4 +74,29 @@ mod test {
         assert_eq!(Direction3D::Up, Direction3D::Down.opposite());
         assert_eq!(Direction3D::Down, Direction3D::Up.opposite());
     }
+
+    #[test]
+    fn rotates_clockwise() {
+        assert_eq!(Direction3D::Right, Direction3D::Top.rotate_clockwise());
+        assert_eq!(Direction3D::Bottom, Direction3D::Right.rotate_clockwise());
+        assert_eq!(Direction3D::Left, Direction3D::Bottom.rotate_clockwise());
+        assert_eq!(Direction3D::Top, Direction3D::Left.rotate_clockwise());
+        assert_eq!(Direction3D::Up, Direction3D::Up.rotate_clockwise());
+    }
+
+    #[test]
+    fn mirrors_horizontally() {
+        assert_eq!(Direction3D::Right, Direction3D::Left.mirror_horizontally());
+        assert_eq!(Direction3D::Left, Direction3D::Right.mirror_horizontally());
+        assert_eq!(Direction3D::Top, Direction3D::Top.mirror_horizontally());
+        assert_eq!(Direction3D::Down, Direction3D::Down.mirror_horizontally());
+    }
+
+    #[test]
+    fn mirrors_vertically() {
+        assert_eq!(Direction3D::Bottom, Direction3D::Top.mirror_vertically());
+        assert_eq!(Direction3D::Top, Direction3D::Bottom.mirror_vertically());
+        assert_eq!(Direction3D::Left, Direction3D::Left.mirror_vertically());
+        assert_eq!(Direction3D::Up, Direction3D::Up.mirror_vertically());
+    }
 }
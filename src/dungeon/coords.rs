@@ -1,8 +1,11 @@
 use std::cmp::{max, min};
 
+use crate::direction::Direction3D;
+
 pub struct DungeonElement;
 
 #[derive(Clone, Default, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DungeonCoordinates {
     pub floor: i32,
     pub col: i32,
@@ -13,9 +16,23 @@ impl DungeonCoordinates {
     pub fn new(floor: i32, col: i32, row: i32) -> Self {
         Self { floor, col, row }
     }
+
+    /// Coordinates of the neighboring room in the given direction; top is the row above, up is the floor above
+    pub fn neighbor(&self, direction: Direction3D) -> Self {
+        match direction {
+            Direction3D::Top => Self::new(self.floor, self.col, self.row - 1),
+            Direction3D::Bottom => Self::new(self.floor, self.col, self.row + 1),
+            Direction3D::Left => Self::new(self.floor, self.col - 1, self.row),
+            Direction3D::Right => Self::new(self.floor, self.col + 1, self.row),
+            Direction3D::Up => Self::new(self.floor + 1, self.col, self.row),
+            Direction3D::Down => Self::new(self.floor - 1, self.col, self.row),
+            Direction3D::None => *self,
+        }
+    }
 }
 
 #[derive(Clone, Default, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StairCoordinates {
     pub lower_floor: DungeonCoordinates,
     pub upper_floor: DungeonCoordinates,
@@ -37,6 +54,24 @@ impl StairCoordinates {
 mod test {
     use super::*;
 
+    #[test]
+    fn finds_neighboring_coordinates() {
+        let sut = DungeonCoordinates::new(0, 2, 1);
+
+        assert_eq!(
+            DungeonCoordinates::new(0, 2, 0),
+            sut.neighbor(Direction3D::Top)
+        );
+        assert_eq!(
+            DungeonCoordinates::new(0, 1, 1),
+            sut.neighbor(Direction3D::Left)
+        );
+        assert_eq!(
+            DungeonCoordinates::new(-1, 2, 1),
+            sut.neighbor(Direction3D::Down)
+        );
+    }
+
     #[test]
     fn builds_stair_coordinates_from_raw_coords() {
         let result = StairCoordinates::from_coords(1, 2, 3, 4);
@@ -0,0 +1,179 @@
+use crate::direction::Direction3D;
+
+use super::{coords::DungeonCoordinates, graph::RoomGraph, layout::DungeonLayout};
+
+/// Weights of the difficulty score of a room.
+/// The score is the walking distance from the first room through exits and stairs,
+/// plus the number of floors between the room and the first room times floor_weight.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DifficultyConfig {
+    /// Difficulty added by every exit between neighboring rooms
+    pub exit_weight: u32,
+    /// Difficulty added by every stair between floors
+    pub stair_weight: u32,
+    /// Difficulty added by every floor between the room and the first room
+    pub floor_weight: u32,
+}
+
+impl Default for DifficultyConfig {
+    fn default() -> Self {
+        Self {
+            exit_weight: 1,
+            stair_weight: 1,
+            floor_weight: 3,
+        }
+    }
+}
+
+/// Difficulty scores of all rooms of the layout reachable from its first room, in the order of the rooms
+pub fn room_difficulties(
+    layout: &DungeonLayout,
+    config: &DifficultyConfig,
+) -> Vec<(DungeonCoordinates, u32)> {
+    let graph = RoomGraph::from_layout(layout);
+    let weight = |direction: Direction3D| match direction {
+        Direction3D::Up | Direction3D::Down => config.stair_weight,
+        _ => config.exit_weight,
+    };
+
+    graph
+        .weighted_distances(layout.first_room, weight)
+        .into_iter()
+        .map(|(coords, distance)| {
+            let depth = coords.floor.abs_diff(layout.first_room.floor);
+            let difficulty = distance.saturating_add(depth.saturating_mul(config.floor_weight));
+            (coords, difficulty)
+        })
+        .collect()
+}
+
+/// Sets the difficulty of every room of the layout
+pub fn assign_difficulties(layout: &mut DungeonLayout, config: &DifficultyConfig) {
+    let difficulties = room_difficulties(layout, config);
+
+    for floor in layout.floors.iter_mut() {
+        for room in floor.rooms.iter_mut() {
+            let coords = DungeonCoordinates::new(floor.floor, room.coords.col, room.coords.row);
+            room.difficulty = difficulties
+                .iter()
+                .find(|(c, _)| *c == coords)
+                .map(|(_, difficulty)| *difficulty)
+                .unwrap_or_default();
+        }
+    }
+}
+
+/// The room with the highest difficulty; on a tie, the room coming last in the layout wins
+pub fn hardest_room(
+    layout: &DungeonLayout,
+    config: &DifficultyConfig,
+) -> Option<DungeonCoordinates> {
+    room_difficulties(layout, config)
+        .into_iter()
+        .max_by_key(|(_, difficulty)| *difficulty)
+        .map(|(coords, _)| coords)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dungeon::{
+        coords::StairCoordinates,
+        graph::test::{create_dungeon_layout, create_room},
+    };
+
+    use super::*;
+
+    #[test]
+    fn scores_distance_and_depth() {
+        let layout = create_layout();
+
+        let result = room_difficulties(&layout, &DifficultyConfig::default());
+
+        assert_eq!(
+            vec![
+                (DungeonCoordinates::new(0, 0, 0), 0),
+                (DungeonCoordinates::new(0, 1, 0), 1),
+                (DungeonCoordinates::new(0, 2, 0), 2),
+                (DungeonCoordinates::new(-1, 2, 0), 6),
+                (DungeonCoordinates::new(-1, 2, 1), 7),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn weighs_exits_stairs_and_floors() {
+        let layout = create_layout();
+        let config = DifficultyConfig {
+            exit_weight: 2,
+            stair_weight: 10,
+            floor_weight: 0,
+        };
+
+        let result = room_difficulties(&layout, &config);
+
+        assert_eq!((DungeonCoordinates::new(-1, 2, 1), 16), result[4]);
+    }
+
+    #[test]
+    fn saturates_large_weights() {
+        let layout = create_layout();
+        let config = DifficultyConfig {
+            exit_weight: u32::MAX,
+            stair_weight: u32::MAX,
+            floor_weight: u32::MAX,
+        };
+
+        let result = room_difficulties(&layout, &config);
+
+        assert_eq!((DungeonCoordinates::new(0, 1, 0), u32::MAX), result[1]);
+        assert_eq!((DungeonCoordinates::new(-1, 2, 1), u32::MAX), result[4]);
+    }
+
+    #[test]
+    fn assigns_difficulties_to_rooms() {
+        let mut layout = create_layout();
+
+        assign_difficulties(&mut layout, &DifficultyConfig::default());
+
+        assert_eq!(2, layout.floors[0].rooms[2].difficulty);
+        assert_eq!(7, layout.floors[1].rooms[1].difficulty);
+        assert_eq!(
+            Some(DungeonCoordinates::new(-1, 2, 1)),
+            hardest_room(&layout, &DifficultyConfig::default())
+        );
+    }
+
+    // floor 0: 0/0 - 1/0 - 2/0, with stairs down from 2/0
+    // floor -1: 2/0 - 2/1
+    fn create_layout() -> DungeonLayout {
+        use Direction3D::*;
+
+        create_dungeon_layout(
+            0,
+            vec![
+                (
+                    0,
+                    vec![
+                        create_room(0, 0, vec![Right]),
+                        create_room(1, 0, vec![Right, Left]),
+                        create_room(2, 0, vec![Left]),
+                    ],
+                ),
+                (
+                    -1,
+                    vec![
+                        create_room(2, 0, vec![Bottom]),
+                        create_room(2, 1, vec![Top]),
+                    ],
+                ),
+            ],
+            vec![StairCoordinates::from_coords(0, 2, -1, 0)],
+        )
+    }
+}
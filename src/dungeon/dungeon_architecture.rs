@@ -3,11 +3,18 @@ use std::ops::Range;
 use rand::prelude::*;
 use rand_pcg::Pcg64;
 
-use crate::{floor::floor_architecture::create_floor_layout, room::math::Position};
+use crate::{
+    error::GenerationError, floor::floor_architecture::try_create_floor_layout,
+    room::math::Position,
+};
 
 use super::{
     coords::{DungeonCoordinates, StairCoordinates},
+    difficulty::{assign_difficulties, hardest_room},
     layout::{DungeonLayout, DungeonLayoutConfig},
+    progression::plan_progression,
+    role::assign_roles,
+    seed::{floor_seed, seeded_rng},
 };
 
 pub struct DungeonArchitect {
@@ -23,12 +30,41 @@ impl Default for DungeonArchitect {
 }
 
 impl DungeonArchitect {
-    pub fn create_dungeon_layout(&self, rng: &mut Pcg64) -> DungeonLayout {
+    pub fn create_dungeon_layout(&self, rng: &mut dyn RngCore) -> DungeonLayout {
+        self.try_create_dungeon_layout(rng)
+            .expect("unable to create dungeon layout")
+    }
+
+    /// Fallible variant of create_dungeon_layout, which rejects invalid layout configs
+    pub fn try_create_dungeon_layout(
+        &self,
+        rng: &mut dyn RngCore,
+    ) -> Result<DungeonLayout, GenerationError> {
+        self.try_create_seeded_dungeon_layout(rng.next_u64())
+    }
+
+    /// Creates the layout of the dungeon with the given seed.
+    /// Every floor is laid out with its own seed derived from the dungeon seed, see seed::floor_seed.
+    pub fn create_seeded_dungeon_layout(&self, seed: u64) -> DungeonLayout {
+        self.try_create_seeded_dungeon_layout(seed)
+            .expect("unable to create dungeon layout")
+    }
+
+    /// Fallible variant of create_seeded_dungeon_layout, which rejects invalid layout configs
+    pub fn try_create_seeded_dungeon_layout(
+        &self,
+        seed: u64,
+    ) -> Result<DungeonLayout, GenerationError> {
+        self.validate()?;
+        let rng = &mut seeded_rng::<Pcg64>(seed);
         let floors_above = rng.gen_range(self.config.floors_above.clone());
         let floors_below = rng.gen_range(self.config.floors_below.clone());
 
-        let mut layout = DungeonLayout::default();
-        self.layout_floor(rng, &mut layout, Position::new(0, 0), 0);
+        let mut layout = DungeonLayout {
+            seed,
+            ..Default::default()
+        };
+        self.layout_floor(&mut layout, Position::new(0, 0), 0)?;
         layout.first_room = layout.coords[0];
 
         // nominate stair coordinates, then build upper & lower floors
@@ -37,41 +73,68 @@ impl DungeonArchitect {
             // add stair coordinates, use stair room row/col coordinates for next room
             // iterate over floors_above and build floor layouts
             self.layout_floors(
-                rng,
                 &mut layout,
                 Position::new(stair_room.row, stair_room.col),
                 1..floors_above + 2,
                 false,
-            );
+            )?;
         }
 
         if floors_below > 0 {
             // add stair coordinates, use stair room row/col coordinates for next room
             // iterate over floors_below and build floor layouts
             self.layout_floors(
-                rng,
                 &mut layout,
                 Position::new(stair_room.row, stair_room.col),
                 1..floors_below + 2,
                 true,
-            );
+            )?;
         }
 
-        // calculate last room (most distanced room from start room at 0/0/0)
+        // the last room is the hardest room to reach from the first room
+        assign_difficulties(&mut layout, &self.config.difficulty);
         layout.last_room =
-            find_distanced_room_in_dungeon(&layout, DungeonCoordinates::new(0, 0, 0));
+            hardest_room(&layout, &self.config.difficulty).unwrap_or(layout.first_room);
 
-        layout
+        assign_roles(&mut layout, &self.config.roles);
+        layout.progression = plan_progression(&layout, &self.config.progression);
+
+        Ok(layout)
+    }
+
+    fn validate(&self) -> Result<(), GenerationError> {
+        let config = &self.config;
+        for (name, range) in [
+            ("floors_above", &config.floors_above),
+            ("floors_below", &config.floors_below),
+            ("floor_size", &config.floor_size),
+        ] {
+            if range.is_empty() {
+                return Err(GenerationError::InvalidConfig(format!(
+                    "{} range {:?} is empty",
+                    name, range
+                )));
+            }
+        }
+
+        // one more floor than the drawn number is laid out, which has to fit into u8
+        if config.floors_above.end > u8::MAX - 1 || config.floors_below.end > u8::MAX - 1 {
+            return Err(GenerationError::InvalidConfig(format!(
+                "at most {} floors above and below are supported",
+                u8::MAX - 2
+            )));
+        }
+
+        Ok(())
     }
 
     fn layout_floors(
         &self,
-        rng: &mut Pcg64,
         layout: &mut DungeonLayout,
         start_room: Position,
         floors: Range<u8>,
         negative_floors: bool,
-    ) {
+    ) -> Result<(), GenerationError> {
         let mut floor_start_room = start_room;
         let mut floor_before = 0;
         for floor_abs in floors {
@@ -89,7 +152,7 @@ impl DungeonArchitect {
             ));
 
             // layout the floor
-            self.layout_floor(rng, layout, floor_start_room, floor);
+            self.layout_floor(layout, floor_start_room, floor)?;
 
             // find the most distanced room as stair room & start room for next floor
             let distanced_room = find_distanced_room_on_floor(
@@ -105,17 +168,21 @@ impl DungeonArchitect {
         }
 
         set_stairs(layout);
+
+        Ok(())
     }
 
     fn layout_floor(
         &self,
-        rng: &mut Pcg64,
         layout: &mut DungeonLayout,
         start_room: Position,
         floor: i32,
-    ) {
+    ) -> Result<(), GenerationError> {
+        let rng = &mut seeded_rng::<Pcg64>(floor_seed(layout.seed, floor));
         let floor_size = rng.gen_range(self.config.floor_size.clone());
-        let ground_floor = create_floor_layout(floor_size, floor, rng, start_room);
+        let mut ground_floor =
+            try_create_floor_layout(floor_size, floor, rng, start_room, self.config.connections)?;
+        ground_floor.dungeon_seed = layout.seed;
         layout.floors.push(ground_floor.clone());
 
         for room in ground_floor.rooms {
@@ -126,6 +193,8 @@ impl DungeonArchitect {
                 row: room.coords.row,
             })
         }
+
+        Ok(())
     }
 }
 
@@ -142,6 +211,7 @@ pub fn find_distanced_room_on_floor(
         .to_owned()
 }
 
+#[deprecated(note = "use difficulty::hardest_room")]
 pub fn find_distanced_room_in_dungeon(
     layout: &DungeonLayout,
     from: DungeonCoordinates,
@@ -177,6 +247,12 @@ fn set_stairs(layout: &mut DungeonLayout) {
 
 #[cfg(test)]
 mod test {
+    use rand_pcg::Pcg64;
+
+    use crate::floor::floor_architecture::{
+        create_floor_layout, FloorConnections, RoomCoordinates,
+    };
+
     use super::*;
 
     #[test]
@@ -186,15 +262,81 @@ mod test {
 
         let result = sut.create_dungeon_layout(&mut rng);
 
-        assert_eq!(10, result.coords.len());
-        assert_eq!(2, result.stairs.len());
-        assert_eq!(DungeonCoordinates::new(-2, -1, 3), result.last_room);
+        assert_eq!(19, result.coords.len());
+        assert_eq!(4, result.stairs.len());
+        assert_eq!(DungeonCoordinates::new(-2, 1, -3), result.last_room);
+        let hardest = result
+            .floors
+            .iter()
+            .flat_map(|f| f.rooms.iter())
+            .map(|r| r.difficulty)
+            .max();
+        assert_eq!(Some(14), hardest);
+    }
+
+    #[test]
+    fn lays_out_floors_independent_of_floor_count() {
+        let sut = DungeonArchitect {
+            config: DungeonLayoutConfig {
+                floors_above: 0..1,
+                floors_below: 0..1,
+                ..Default::default()
+            },
+        };
+        let other = DungeonArchitect {
+            config: DungeonLayoutConfig {
+                floors_above: 3..4,
+                floors_below: 3..4,
+                ..Default::default()
+            },
+        };
+
+        let result = sut.create_seeded_dungeon_layout(5);
+        let other_result = other.create_seeded_dungeon_layout(5);
+
+        assert_eq!(5, result.seed);
+        assert_eq!(1, result.floors.len());
+        assert_eq!(9, other_result.floors.len());
+        let coords = |layout: &DungeonLayout| -> Vec<RoomCoordinates> {
+            layout.floors[0].rooms.iter().map(|r| r.coords).collect()
+        };
+        assert_eq!(coords(&result), coords(&other_result));
+    }
+
+    #[test]
+    fn rejects_empty_config_ranges() {
+        let sut = DungeonArchitect {
+            config: DungeonLayoutConfig {
+                floor_size: 3..3,
+                ..Default::default()
+            },
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+
+        let result = sut.try_create_dungeon_layout(&mut rng);
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn rejects_too_many_floors() {
+        let sut = DungeonArchitect {
+            config: DungeonLayoutConfig {
+                floors_above: 0..u8::MAX,
+                ..Default::default()
+            },
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+
+        let result = sut.try_create_dungeon_layout(&mut rng);
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
     }
 
     #[test]
     fn finds_most_distanced_room_on_floor_by_coordinates() {
         let mut rng = Pcg64::seed_from_u64(1);
-        let floor = create_floor_layout(7, 0, &mut rng, Position::new(0, 0));
+        let floor = create_floor_layout(7, 0, &mut rng, Position::new(0, 0), FloorConnections::All);
         let layout = DungeonLayout {
             coords: floor
                 .rooms
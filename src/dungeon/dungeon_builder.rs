@@ -1,14 +1,21 @@
 use crate::{
     direction::Direction3D,
-    floor::floor_architecture::FloorLayout,
-    room::{room_builder::RoomBuilder, tile::DungeonTile},
+    error::GenerationError,
+    floor::floor_architecture::{FloorLayout, FloorRoom, RoomCoordinates},
+    room::{
+        distance::DistanceMap, pathfinding::walking_cost, room_builder::RoomBuilder,
+        tile::DungeonTile,
+    },
 };
 
 use super::{
     coords::DungeonCoordinates,
     dungeon_architecture::DungeonArchitect,
     layout::{DungeonLayout, DungeonLayoutConfig},
+    role::RoomRole,
     room::ArrangedDungeonRoom,
+    seed::{room_seed, seeded_rng},
+    selection::BuilderSelection,
 };
 use rand::prelude::*;
 use rand_pcg::Pcg64;
@@ -16,47 +23,240 @@ use rand_pcg::Pcg64;
 /// Builder trait for creating dungeons.
 /// The building result is a vector of rooms with dungeon coordinates, exits, and stairs.
 ///
+/// Any random number generator can be used; the same generator state always creates the same dungeon.
+/// The generator only draws the dungeon seed. Every floor layout and every room gets its own seed derived from it (see the seed module),
+/// so changing a single room leaves the rest of the dungeon unchanged.
+///
+/// The rooms are created with generators of type R seeded from their room seeds, Pcg64 unless the implementation chooses another one,
+/// e.g. `impl DungeonBuilder<ChaCha8Rng> for MyBuilder` to create all rooms with the generator of the game engine.
+///
 /// The intended use is for the concrete implementation to provide a layout configuration and set the room builders for the dungeon.
-pub trait DungeonBuilder {
-    fn layout(&self, rng: &mut Pcg64) -> DungeonLayout {
+pub trait DungeonBuilder<R: SeedableRng + RngCore = Pcg64> {
+    fn layout(&self, rng: &mut dyn RngCore) -> DungeonLayout {
+        self.seeded_layout(rng.next_u64())
+    }
+
+    /// Fallible variant of layout, which rejects invalid layout configs
+    fn try_layout(&self, rng: &mut dyn RngCore) -> Result<DungeonLayout, GenerationError> {
+        self.try_seeded_layout(rng.next_u64())
+    }
+
+    /// Creates the layout of the dungeon with the given seed
+    fn seeded_layout(&self, seed: u64) -> DungeonLayout {
+        let architect = DungeonArchitect {
+            config: self.get_layout_config(),
+        };
+
+        architect.create_seeded_dungeon_layout(seed)
+    }
+
+    /// Fallible variant of seeded_layout, which rejects invalid layout configs
+    fn try_seeded_layout(&self, seed: u64) -> Result<DungeonLayout, GenerationError> {
         let architect = DungeonArchitect {
             config: self.get_layout_config(),
         };
 
-        architect.create_dungeon_layout(rng)
+        architect.try_create_seeded_dungeon_layout(seed)
     }
 
-    fn create_dungeon_floor(
+    /// Creates the rooms of a floor.
+    /// Random decisions should use the seeds derived from the dungeon seed of the floor layout, like create_rooms does.
+    fn create_dungeon_floor(&self, floor_layout: &FloorLayout) -> Vec<ArrangedDungeonRoom>;
+
+    /// Fallible variant of create_dungeon_floor; implementations which can fail override it, e.g. by using try_create_rooms
+    fn try_create_dungeon_floor(
         &self,
-        rng: &mut Pcg64,
         floor_layout: &FloorLayout,
-    ) -> Vec<ArrangedDungeonRoom>;
+    ) -> Result<Vec<ArrangedDungeonRoom>, GenerationError> {
+        Ok(self.create_dungeon_floor(floor_layout))
+    }
 
     fn get_layout_config(&self) -> DungeonLayoutConfig;
 
+    /// Creates the rooms of a floor with randomly chosen room builders.
+    /// Every room uses its own random number generator of type R, seeded from the dungeon seed and its coordinates.
     fn create_rooms(
         &self,
-        rng: &mut Pcg64,
         room_builders: Vec<Box<dyn RoomBuilder>>,
         floor_layout: &FloorLayout,
     ) -> Vec<ArrangedDungeonRoom> {
-        let mut rooms = vec![];
-        for room_config in &floor_layout.rooms {
-            let room_builder_idx = rng.gen_range(0..room_builders.len());
-            let random_builder = &room_builders[room_builder_idx];
-            let mut room = random_builder.create_room(rng, &room_config);
-            room.pathing();
-            let mut arranged = ArrangedDungeonRoom::from(&room);
-            self.arrange_room(&mut arranged, &room.exit_directions);
-            arranged.dungeon_coords = DungeonCoordinates {
-                floor: floor_layout.floor,
-                col: room_config.coords.col,
-                row: room_config.coords.row,
+        self.try_create_rooms(room_builders, floor_layout)
+            .expect("unable to create rooms")
+    }
+
+    /// Fallible variant of create_rooms, which fails if a room builder fails or creates a room without walkable tiles
+    fn try_create_rooms(
+        &self,
+        room_builders: Vec<Box<dyn RoomBuilder>>,
+        floor_layout: &FloorLayout,
+    ) -> Result<Vec<ArrangedDungeonRoom>, GenerationError> {
+        if room_builders.is_empty() {
+            return Err(GenerationError::InvalidConfig(
+                "at least one room builder is needed".to_string(),
+            ));
+        }
+
+        create_floor_rooms(self, floor_layout, |rng, _| {
+            Ok(room_builders[rng.gen_range(0..room_builders.len())].as_ref())
+        })
+    }
+
+    /// Creates the rooms of a floor with the room builders chosen by the selection policy,
+    /// e.g. a RuleSelection with weighted builders, per floor builders or builders for rooms with stairs
+    fn create_rooms_with(
+        &self,
+        room_builders: Vec<Box<dyn RoomBuilder>>,
+        selection: &dyn BuilderSelection,
+        floor_layout: &FloorLayout,
+    ) -> Vec<ArrangedDungeonRoom> {
+        self.try_create_rooms_with(room_builders, selection, floor_layout)
+            .expect("unable to create rooms")
+    }
+
+    /// Fallible variant of create_rooms_with, which also fails if the selection policy chooses no room builder for a room
+    fn try_create_rooms_with(
+        &self,
+        room_builders: Vec<Box<dyn RoomBuilder>>,
+        selection: &dyn BuilderSelection,
+        floor_layout: &FloorLayout,
+    ) -> Result<Vec<ArrangedDungeonRoom>, GenerationError> {
+        let mut created = vec![];
+        create_floor_rooms(self, floor_layout, |rng, room_config| {
+            let coords = DungeonCoordinates::new(
+                floor_layout.floor,
+                room_config.coords.col,
+                room_config.coords.row,
+            );
+            let builder_idx = selection
+                .select(rng, floor_layout, room_config, &created)
+                .filter(|idx| *idx < room_builders.len())
+                .ok_or_else(|| {
+                    GenerationError::InvalidConfig(format!(
+                        "no room builder selected for the room at floor {}, column {}, row {}",
+                        coords.floor, coords.col, coords.row
+                    ))
+                })?;
+            created.push((coords, builder_idx));
+
+            Ok(room_builders[builder_idx].as_ref())
+        })
+    }
+
+    /// Creates the rooms of a floor, choosing the room builders by the roles of the rooms.
+    /// Every room is created by a random one of the builders for its role.
+    /// Rooms without builders for their role use the builders for normal rooms, or any builder if there are none.
+    fn create_rooms_by_role(
+        &self,
+        room_builders: Vec<(RoomRole, Box<dyn RoomBuilder>)>,
+        floor_layout: &FloorLayout,
+    ) -> Vec<ArrangedDungeonRoom> {
+        self.try_create_rooms_by_role(room_builders, floor_layout)
+            .expect("unable to create rooms")
+    }
+
+    /// Fallible variant of create_rooms_by_role
+    fn try_create_rooms_by_role(
+        &self,
+        room_builders: Vec<(RoomRole, Box<dyn RoomBuilder>)>,
+        floor_layout: &FloorLayout,
+    ) -> Result<Vec<ArrangedDungeonRoom>, GenerationError> {
+        if room_builders.is_empty() {
+            return Err(GenerationError::InvalidConfig(
+                "at least one room builder is needed".to_string(),
+            ));
+        }
+
+        create_floor_rooms(self, floor_layout, |rng, room_config| {
+            let for_role = |role: RoomRole| -> Vec<&dyn RoomBuilder> {
+                room_builders
+                    .iter()
+                    .filter(|(r, _)| *r == role)
+                    .map(|(_, b)| b.as_ref())
+                    .collect()
             };
-            rooms.push(arranged);
+
+            let mut candidates = for_role(room_config.role);
+            if candidates.is_empty() {
+                candidates = for_role(RoomRole::Normal);
+            }
+            if candidates.is_empty() {
+                candidates = room_builders.iter().map(|(_, b)| b.as_ref()).collect();
+            }
+
+            Ok(candidates[rng.gen_range(0..candidates.len())])
+        })
+    }
+
+    /// Creates a single room with the given room builder and places its exits and stairs
+    fn try_create_arranged_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_builder: &dyn RoomBuilder,
+        room_config: &FloorRoom,
+        dungeon_coords: DungeonCoordinates,
+    ) -> Result<ArrangedDungeonRoom, GenerationError> {
+        let mut room = room_builder.try_create_room(rng, room_config)?;
+        room.pathing();
+        if room.pathing.is_empty() {
+            return Err(GenerationError::NoWalkableTiles);
         }
 
-        rooms
+        let mut arranged = ArrangedDungeonRoom::from(&room);
+        self.arrange_room(&mut arranged, &room.exit_directions);
+        arranged.dungeon_coords = dungeon_coords;
+        arranged.role = room_config.role;
+        arranged.difficulty = room_config.difficulty;
+
+        Ok(arranged)
+    }
+
+    /// Rebuilds the room at the given coordinates with the given room builder, leaving all other rooms unchanged.
+    /// The new room keeps the stairs, the role and the difficulty of the old room and gets an exit towards every neighbor with an exit facing it,
+    /// so it stays connected to the rest of the floor.
+    fn regenerate_room(
+        &self,
+        rng: &mut dyn RngCore,
+        rooms: &mut [ArrangedDungeonRoom],
+        coords: DungeonCoordinates,
+        room_builder: &dyn RoomBuilder,
+    ) -> Result<(), GenerationError> {
+        let room_idx = rooms
+            .iter()
+            .position(|r| r.dungeon_coords == coords)
+            .ok_or(GenerationError::RoomNotFound(coords))?;
+
+        // same order of exits as in the floor layout
+        let exits = [
+            Direction3D::Right,
+            Direction3D::Left,
+            Direction3D::Top,
+            Direction3D::Bottom,
+        ]
+        .into_iter()
+        .filter(|direction| {
+            rooms.iter().any(|r| {
+                r.dungeon_coords == coords.neighbor(*direction)
+                    && !r.exit_tiles(direction.opposite()).is_empty()
+            })
+        })
+        .collect();
+
+        let old_room = &rooms[room_idx];
+        let room_config = FloorRoom {
+            coords: RoomCoordinates {
+                col: coords.col,
+                row: coords.row,
+            },
+            exits,
+            stair_up: old_room.stair_up,
+            stair_down: old_room.stair_down,
+            role: old_room.role,
+            difficulty: old_room.difficulty,
+        };
+
+        rooms[room_idx] = self.try_create_arranged_room(rng, room_builder, &room_config, coords)?;
+
+        Ok(())
     }
 
     fn arrange_room(&self, room: &mut ArrangedDungeonRoom, directions: &Vec<Direction3D>) {
@@ -66,7 +266,8 @@ pub trait DungeonBuilder {
 
     fn set_exits(&self, room: &mut ArrangedDungeonRoom, directions: &Vec<Direction3D>) {
         if room.tiles.contains(&DungeonTile::Exit) {
-            // exits were already set by room builder - no need to set exits here
+            // exits were already set by room builder - only list them
+            room.list_border_exits();
             return;
         }
 
@@ -82,45 +283,157 @@ pub trait DungeonBuilder {
         }
     }
 
+    /// Places the stairs of a room on the floor tiles farthest from its exits.
+    /// If the room has both stairs, the second one is placed far away from the exits and the first stairs.
     fn set_all_stairs(&self, room: &mut ArrangedDungeonRoom) {
-        let mut target_path_tile = room.pathing.len() / 3;
+        let mut sources: Vec<usize> = room
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| **t == DungeonTile::Exit)
+            .map(|(idx, _)| idx)
+            .chain(room.entry.map(|(idx, _)| idx))
+            .collect();
+        if sources.is_empty() {
+            sources.extend(room.pathing.first());
+        }
+
         if room.stair_down {
-            self.set_stairs(room, DungeonTile::StairsDown, target_path_tile);
-            target_path_tile *= 2;
+            sources.extend(self.set_stairs(room, DungeonTile::StairsDown, &sources));
         }
         if room.stair_up {
-            self.set_stairs(room, DungeonTile::StairsUp, target_path_tile);
+            self.set_stairs(room, DungeonTile::StairsUp, &sources);
         }
     }
 
+    /// Turns the floor tile with the highest walking distance to the sources into a stair tile.
+    /// Returns the index of the stair tile, if there was a floor tile to place it on.
     fn set_stairs(
         &self,
         room: &mut ArrangedDungeonRoom,
         stair_tile: DungeonTile,
-        target_path_tile: usize,
-    ) {
-        let mut target_tile = -1;
-        for path_tile in room.pathing.iter().skip(target_path_tile).step_by(2) {
-            if room.tiles[*path_tile] == DungeonTile::Floor {
-                target_tile = *path_tile as i32;
-                break;
-            }
-        }
+        sources: &[usize],
+    ) -> Option<usize> {
+        let distances = DistanceMap::new(room, sources, walking_cost);
+        let target_tile = distances.farthest_where(|idx| room.tiles[idx] == DungeonTile::Floor)?;
+        room.tiles[target_tile] = stair_tile;
 
-        if target_tile > 0 {
-            room.tiles[target_tile as usize] = stair_tile;
-        }
+        Some(target_tile)
+    }
+}
+
+// Creates the rooms of a floor with the room builders chosen for them.
+// Every room uses its own random number generator of type R, seeded from the dungeon seed and its coordinates.
+fn create_floor_rooms<'a, R, B, C>(
+    builder: &B,
+    floor_layout: &FloorLayout,
+    mut choose_builder: C,
+) -> Result<Vec<ArrangedDungeonRoom>, GenerationError>
+where
+    R: SeedableRng + RngCore,
+    B: DungeonBuilder<R> + ?Sized,
+    C: FnMut(&mut dyn RngCore, &FloorRoom) -> Result<&'a dyn RoomBuilder, GenerationError>,
+{
+    let mut rooms = vec![];
+    for room_config in &floor_layout.rooms {
+        let dungeon_coords = DungeonCoordinates {
+            floor: floor_layout.floor,
+            col: room_config.coords.col,
+            row: room_config.coords.row,
+        };
+        let rng = &mut seeded_rng::<R>(room_seed(floor_layout.dungeon_seed, dungeon_coords));
+
+        let room_builder = choose_builder(rng, room_config)?;
+        rooms.push(builder.try_create_arranged_room(
+            rng,
+            room_builder,
+            room_config,
+            dungeon_coords,
+        )?);
     }
+
+    Ok(rooms)
 }
 
 #[cfg(test)]
 pub mod test {
     use std::{fs, path::PathBuf};
 
-    use crate::{dungeon::print::print_dungeon, room::automata::AutomataRoomBuilder};
+    use crate::{
+        dungeon::{
+            print::print_dungeon,
+            selection::{BuilderRule, RuleSelection},
+            stitch::stitch_floor,
+        },
+        room::{automata::AutomataRoomBuilder, grid::GridRoomBuilder, room::DungeonRoom},
+    };
+
+    use rand_pcg::Pcg64;
 
     use super::*;
 
+    #[test]
+    pub fn places_stairs_far_away_from_exits() {
+        let sut = DummyDungeonBuilder {};
+        let mut room = ArrangedDungeonRoom {
+            rows: 3,
+            columns: 5,
+            tiles: vec![DungeonTile::Floor; 15],
+            pathing: (0..15).collect(),
+            stair_down: true,
+            stair_up: true,
+            ..Default::default()
+        };
+        room.tiles[5] = DungeonTile::Exit;
+
+        sut.set_all_stairs(&mut room);
+
+        assert_eq!(DungeonTile::StairsDown, room.tiles[4]);
+        assert_eq!(DungeonTile::StairsUp, room.tiles[12]);
+    }
+
+    #[test]
+    pub fn places_stairs_in_rooms_without_exits() {
+        let sut = DummyDungeonBuilder {};
+        let mut room = ArrangedDungeonRoom {
+            rows: 2,
+            columns: 3,
+            tiles: vec![DungeonTile::Floor; 6],
+            pathing: (0..6).collect(),
+            stair_up: true,
+            ..Default::default()
+        };
+
+        sut.set_all_stairs(&mut room);
+
+        assert_eq!(DungeonTile::StairsUp, room.tiles[5]);
+    }
+
+    #[test]
+    pub fn lists_exits_placed_by_room_builder() {
+        let sut = DummyDungeonBuilder {};
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Bottom, Direction3D::Right],
+            ..Default::default()
+        };
+
+        let room = sut
+            .try_create_arranged_room(
+                &mut Pcg64::seed_from_u64(1),
+                &GridRoomBuilder::default(),
+                &room_config,
+                DungeonCoordinates::default(),
+            )
+            .unwrap();
+
+        let mut directions: Vec<Direction3D> = room.exits.iter().map(|(_, d)| *d).collect();
+        directions.sort_by_key(|d| *d as u8);
+        assert_eq!(vec![Direction3D::Bottom, Direction3D::Right], directions);
+        for (idx, _) in room.exits.iter() {
+            assert_eq!(DungeonTile::Exit, room.tiles[*idx]);
+        }
+    }
+
     #[test]
     pub fn creates_printable_dungeon() {
         let sut = DummyDungeonBuilder {};
@@ -134,6 +447,211 @@ pub mod test {
         assert_linewise_eq(&expected_output, &output);
     }
 
+    #[test]
+    pub fn creates_rooms_with_other_rngs() {
+        let sut = StdRngDungeonBuilder {};
+        let layout = sut.seeded_layout(7);
+        let floor = &layout.floors[0];
+
+        let result = sut.create_dungeon_floor(floor);
+
+        // every room is created by a StdRng seeded with its room seed
+        let room_builder = create_automata_builder();
+        for (room, room_config) in result.iter().zip(floor.rooms.iter()) {
+            let mut rng = StdRng::seed_from_u64(room_seed(layout.seed, room.dungeon_coords));
+            // the room builder is chosen first, among one builder
+            rng.gen_range(0..1_usize);
+            let expected = sut
+                .try_create_arranged_room(&mut rng, &room_builder, room_config, room.dungeon_coords)
+                .unwrap();
+            assert!(*room == expected);
+        }
+        assert!(result != DummyDungeonBuilder {}.create_dungeon_floor(floor));
+    }
+
+    #[test]
+    pub fn creates_rooms_independent_of_other_rooms() {
+        let sut = DummyDungeonBuilder {};
+        let layout = sut.seeded_layout(7);
+        let mut floor = layout.floors[0].clone();
+        let all_rooms = sut.create_dungeon_floor(&floor);
+
+        floor.rooms.remove(0);
+        let result = sut.create_dungeon_floor(&floor);
+
+        assert!(all_rooms[1..] == result[..]);
+    }
+
+    #[test]
+    pub fn creates_rooms_by_role() {
+        let sut = DummyDungeonBuilder {};
+        let layout = sut.seeded_layout(1);
+        let floor = layout
+            .floors
+            .iter()
+            .find(|f| f.floor == layout.first_room.floor)
+            .unwrap();
+        let start_builder = GridRoomBuilder::default();
+        let start_rows = start_builder.get_rows();
+
+        let result = sut.create_rooms_by_role(
+            vec![
+                (RoomRole::Start, Box::new(start_builder)),
+                (RoomRole::Normal, Box::new(AutomataRoomBuilder::default())),
+            ],
+            floor,
+        );
+
+        for room in result.iter() {
+            let is_start = room.dungeon_coords == layout.first_room;
+            assert_eq!(is_start, room.role == RoomRole::Start);
+            assert_eq!(is_start, room.rows == start_rows);
+        }
+    }
+
+    #[test]
+    pub fn creates_rooms_with_selected_builders() {
+        let sut = DummyDungeonBuilder {};
+        let floor = sut.seeded_layout(1).floors[0].clone();
+        let grid_builder = GridRoomBuilder::default();
+        let grid_rows = grid_builder.get_rows();
+        let selection = RuleSelection {
+            rules: vec![
+                BuilderRule::new(0, 1),
+                BuilderRule::new(1, 1),
+                BuilderRule {
+                    floors: Some(1..10),
+                    ..BuilderRule::new(2, 100)
+                },
+            ],
+            distinct_neighbors: true,
+        };
+
+        let result = sut.create_rooms_with(
+            vec![
+                Box::new(AutomataRoomBuilder::default()),
+                Box::new(grid_builder),
+                Box::new(WallRoomBuilder {}),
+            ],
+            &selection,
+            &floor,
+        );
+
+        for room in result.iter() {
+            for neighbor in result.iter() {
+                let adjacent = (room.dungeon_coords.col - neighbor.dungeon_coords.col).abs()
+                    + (room.dungeon_coords.row - neighbor.dungeon_coords.row).abs()
+                    == 1;
+                if adjacent {
+                    assert_ne!(room.rows == grid_rows, neighbor.rows == grid_rows);
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn fails_to_create_rooms_without_selected_builder() {
+        let sut = DummyDungeonBuilder {};
+
+        let result = sut.try_create_rooms_with(
+            vec![Box::new(AutomataRoomBuilder::default())],
+            &RuleSelection::default(),
+            &create_floor_layout(),
+        );
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    pub fn regenerates_room_connected_to_its_neighbors() {
+        let sut = DummyDungeonBuilder {};
+        let mut rooms = sut.create_dungeon(&mut Pcg64::seed_from_u64(1));
+        let old_rooms = rooms.clone();
+        let stair_room = rooms
+            .iter()
+            .find(|r| r.stair_up && r.stair_down)
+            .unwrap()
+            .dungeon_coords;
+
+        sut.regenerate_room(
+            &mut Pcg64::seed_from_u64(2),
+            &mut rooms,
+            stair_room,
+            &GridRoomBuilder::default(),
+        )
+        .unwrap();
+
+        for (room, old_room) in rooms.iter().zip(old_rooms.iter()) {
+            assert!((room == old_room) != (room.dungeon_coords == stair_room));
+        }
+        let room = rooms
+            .iter()
+            .find(|r| r.dungeon_coords == stair_room)
+            .unwrap();
+        assert!(room.stair_up && room.stair_down);
+        assert!(room.tiles.contains(&DungeonTile::StairsUp));
+        assert!(room.tiles.contains(&DungeonTile::StairsDown));
+
+        let floor = stitch_floor(
+            rooms
+                .iter()
+                .filter(|r| r.dungeon_coords.floor == stair_room.floor)
+                .collect(),
+        );
+        let start = floor.from_room_idx(room, room.pathing[0]).unwrap();
+        let distances = DistanceMap::new(&floor, &[start], walking_cost);
+        for other in rooms
+            .iter()
+            .filter(|r| r.dungeon_coords.floor == stair_room.floor)
+        {
+            let exit = floor.from_room_idx(other, other.pathing[0]).unwrap();
+            assert!(distances.distance(exit).is_some());
+        }
+    }
+
+    #[test]
+    pub fn fails_to_regenerate_missing_room() {
+        let sut = DummyDungeonBuilder {};
+        let mut rooms = sut.create_dungeon(&mut Pcg64::seed_from_u64(1));
+        let coords = DungeonCoordinates::new(100, 0, 0);
+
+        let result = sut.regenerate_room(
+            &mut Pcg64::seed_from_u64(2),
+            &mut rooms,
+            coords,
+            &GridRoomBuilder::default(),
+        );
+
+        assert_eq!(Err(GenerationError::RoomNotFound(coords)), result);
+    }
+
+    #[test]
+    pub fn fails_to_create_rooms_without_room_builders() {
+        let sut = DummyDungeonBuilder {};
+
+        let result = sut.try_create_rooms(vec![], &create_floor_layout());
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    pub fn fails_to_create_rooms_without_walkable_tiles() {
+        let sut = DummyDungeonBuilder {};
+
+        let result =
+            sut.try_create_rooms(vec![Box::new(WallRoomBuilder {})], &create_floor_layout());
+
+        assert!(matches!(result, Err(GenerationError::NoWalkableTiles)));
+    }
+
+    fn create_floor_layout() -> FloorLayout {
+        FloorLayout {
+            rooms: vec![FloorRoom::default()],
+            floor: 0,
+            dungeon_seed: 0,
+        }
+    }
+
     fn resource_file_content(filename: &str) -> String {
         let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         test_file.push("resources/test/");
@@ -163,17 +681,39 @@ pub mod test {
         }
     }
 
+    /// A room builder creating rooms without any walkable tile
+    struct WallRoomBuilder {}
+
+    impl RoomBuilder for WallRoomBuilder {
+        fn create_room(&self, _rng: &mut dyn RngCore, _room_config: &FloorRoom) -> DungeonRoom {
+            DungeonRoom {
+                tiles: vec![DungeonTile::Wall; 9],
+                rows: 3,
+                columns: 3,
+                ..Default::default()
+            }
+        }
+
+        fn get_rows(&self) -> usize {
+            3
+        }
+
+        fn get_cols(&self) -> usize {
+            3
+        }
+    }
+
     /// A dungeon builder implementation for testing purposes
     struct DummyDungeonBuilder {}
 
     impl DummyDungeonBuilder {
-        pub fn create_dungeon(&self, rng: &mut rand_pcg::Pcg64) -> Vec<ArrangedDungeonRoom> {
+        pub fn create_dungeon(&self, rng: &mut dyn RngCore) -> Vec<ArrangedDungeonRoom> {
             let layout = self.layout(rng);
 
             let mut all_rooms: Vec<ArrangedDungeonRoom> = vec![];
             for floor in layout.floors {
                 let mut rooms =
-                    self.create_rooms(rng, vec![Box::new(AutomataRoomBuilder::default())], &floor);
+                    self.create_rooms(vec![Box::new(AutomataRoomBuilder::default())], &floor);
                 all_rooms.append(&mut rooms);
             }
 
@@ -184,17 +724,9 @@ pub mod test {
     impl DungeonBuilder for DummyDungeonBuilder {
         fn create_dungeon_floor(
             &self,
-            rng: &mut rand_pcg::Pcg64,
             floor_layout: &crate::floor::floor_architecture::FloorLayout,
         ) -> Vec<crate::dungeon::room::ArrangedDungeonRoom> {
-            let room_builder = AutomataRoomBuilder {
-                rows: 20,
-                cols: 20,
-                wall_percent: 40,
-                iterations: 2,
-            };
-
-            self.create_rooms(rng, vec![Box::new(room_builder)], floor_layout)
+            self.create_rooms(vec![Box::new(create_automata_builder())], floor_layout)
         }
 
         fn get_layout_config(&self) -> crate::dungeon::layout::DungeonLayoutConfig {
@@ -203,4 +735,27 @@ pub mod test {
             }
         }
     }
+
+    /// A dungeon builder creating its rooms with StdRng instead of Pcg64
+    struct StdRngDungeonBuilder {}
+
+    impl DungeonBuilder<StdRng> for StdRngDungeonBuilder {
+        fn create_dungeon_floor(&self, floor_layout: &FloorLayout) -> Vec<ArrangedDungeonRoom> {
+            self.create_rooms(vec![Box::new(create_automata_builder())], floor_layout)
+        }
+
+        fn get_layout_config(&self) -> DungeonLayoutConfig {
+            DungeonLayoutConfig::default()
+        }
+    }
+
+    fn create_automata_builder() -> AutomataRoomBuilder {
+        AutomataRoomBuilder {
+            rows: 20,
+            cols: 20,
+            wall_percent: 40,
+            iterations: 2,
+            ..Default::default()
+        }
+    }
 }
@@ -0,0 +1,488 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::{direction::Direction3D, floor::floor_architecture::FloorLayout};
+
+use super::{coords::DungeonCoordinates, layout::DungeonLayout};
+
+/// Connections between the rooms of a dungeon or floor.
+/// Rooms are connected by exits to neighboring rooms on the same floor and by stairs to rooms on other floors.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct RoomGraph {
+    pub rooms: Vec<DungeonCoordinates>,
+    /// Connections of every room, as indices into rooms with the direction leading to the connected room
+    pub edges: Vec<Vec<(usize, Direction3D)>>,
+}
+
+impl RoomGraph {
+    /// Builds the graph of all rooms of the dungeon, connected by their exits and stairs
+    pub fn from_layout(layout: &DungeonLayout) -> Self {
+        let mut graph = Self::default();
+        for floor in layout.floors.iter() {
+            graph.add_floor(floor);
+        }
+
+        for stairs in layout.stairs.iter() {
+            graph.connect(stairs.lower_floor, Direction3D::Up, stairs.upper_floor);
+            graph.connect(stairs.upper_floor, Direction3D::Down, stairs.lower_floor);
+        }
+
+        graph
+    }
+
+    /// Builds the graph of the rooms of a single floor, connected by their exits
+    pub fn from_floor(floor: &FloorLayout) -> Self {
+        let mut graph = Self::default();
+        graph.add_floor(floor);
+
+        graph
+    }
+
+    pub fn contains(&self, coords: DungeonCoordinates) -> bool {
+        self.idx(coords).is_some()
+    }
+
+    /// Rooms connected to the given room, with the direction leading to them
+    pub fn neighbors(&self, coords: DungeonCoordinates) -> Vec<(DungeonCoordinates, Direction3D)> {
+        match self.idx(coords) {
+            Some(idx) => self.edges[idx]
+                .iter()
+                .map(|(neighbor, direction)| (self.rooms[*neighbor], *direction))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Number of connections of the given room
+    pub fn degree(&self, coords: DungeonCoordinates) -> usize {
+        self.idx(coords)
+            .map(|idx| self.edges[idx].len())
+            .unwrap_or(0)
+    }
+
+    /// Dead ends, i.e. rooms with a single connection
+    pub fn leaves(&self) -> Vec<DungeonCoordinates> {
+        self.rooms
+            .iter()
+            .zip(self.edges.iter())
+            .filter(|(_, edges)| edges.len() == 1)
+            .map(|(coords, _)| *coords)
+            .collect()
+    }
+
+    /// Rooms reachable from the given room, including itself, without passing the blocked connections.
+    /// Blocked connections are pairs of rooms, blocked in both directions.
+    pub fn reachable(
+        &self,
+        from: DungeonCoordinates,
+        blocked: &[(DungeonCoordinates, DungeonCoordinates)],
+    ) -> Vec<DungeonCoordinates> {
+        let start = match self.idx(from) {
+            Some(idx) => idx,
+            None => return vec![],
+        };
+        let is_blocked = |a: usize, b: usize| {
+            blocked.iter().any(|(first, second)| {
+                (self.rooms[a] == *first && self.rooms[b] == *second)
+                    || (self.rooms[a] == *second && self.rooms[b] == *first)
+            })
+        };
+
+        let mut visited = vec![false; self.rooms.len()];
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(idx) = stack.pop() {
+            for (neighbor, _) in self.edges[idx].iter() {
+                if !visited[*neighbor] && !is_blocked(idx, *neighbor) {
+                    visited[*neighbor] = true;
+                    stack.push(*neighbor);
+                }
+            }
+        }
+
+        self.rooms
+            .iter()
+            .zip(visited.iter())
+            .filter(|(_, visited)| **visited)
+            .map(|(coords, _)| *coords)
+            .collect()
+    }
+
+    /// Distances of all rooms reachable from the given room, in the order of rooms.
+    /// Every connection costs the weight of its direction, so e.g. stairs can count more than exits.
+    pub fn weighted_distances<W: Fn(Direction3D) -> u32>(
+        &self,
+        from: DungeonCoordinates,
+        weight: W,
+    ) -> Vec<(DungeonCoordinates, u32)> {
+        let start = match self.idx(from) {
+            Some(idx) => idx,
+            None => return vec![],
+        };
+
+        // dijkstra, the graphs are small enough to skip outdated heap entries instead of updating them
+        let mut distances: Vec<Option<u32>> = vec![None; self.rooms.len()];
+        let mut open = BinaryHeap::from([Reverse((0, start))]);
+        distances[start] = Some(0);
+        while let Some(Reverse((distance, idx))) = open.pop() {
+            if distances[idx].is_some_and(|d| d < distance) {
+                continue;
+            }
+
+            for (neighbor, direction) in self.edges[idx].iter() {
+                let next = distance.saturating_add(weight(*direction));
+                if distances[*neighbor].is_none_or(|d| next < d) {
+                    distances[*neighbor] = Some(next);
+                    open.push(Reverse((next, *neighbor)));
+                }
+            }
+        }
+
+        self.rooms
+            .iter()
+            .zip(distances.iter())
+            .filter_map(|(coords, distance)| distance.map(|d| (*coords, d)))
+            .collect()
+    }
+
+    /// Rooms on a path with the fewest connections between two rooms, including both of them.
+    /// Returns None, if there is no path between the rooms.
+    pub fn shortest_path(
+        &self,
+        from: DungeonCoordinates,
+        to: DungeonCoordinates,
+    ) -> Option<Vec<DungeonCoordinates>> {
+        let start = self.idx(from)?;
+        let target = self.idx(to)?;
+
+        // breadth first search, remembering the room every room was reached from
+        let mut previous: Vec<Option<usize>> = vec![None; self.rooms.len()];
+        let mut visited = vec![false; self.rooms.len()];
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+        while let Some(idx) = queue.pop_front() {
+            if idx == target {
+                break;
+            }
+
+            for (neighbor, _) in self.edges[idx].iter() {
+                if !visited[*neighbor] {
+                    visited[*neighbor] = true;
+                    previous[*neighbor] = Some(idx);
+                    queue.push_back(*neighbor);
+                }
+            }
+        }
+
+        if !visited[target] {
+            return None;
+        }
+
+        let mut path = vec![self.rooms[target]];
+        let mut current = target;
+        while let Some(idx) = previous[current] {
+            path.push(self.rooms[idx]);
+            current = idx;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    /// Rooms which disconnect other rooms from each other if they are removed, like a single corridor between two areas
+    pub fn articulation_points(&self) -> Vec<DungeonCoordinates> {
+        let mut search = ArticulationSearch {
+            order: vec![None; self.rooms.len()],
+            low: vec![0; self.rooms.len()],
+            is_articulation: vec![false; self.rooms.len()],
+            counter: 0,
+        };
+
+        for idx in 0..self.rooms.len() {
+            if search.order[idx].is_none() {
+                search.visit(self, idx, None);
+            }
+        }
+
+        self.rooms
+            .iter()
+            .zip(search.is_articulation.iter())
+            .filter(|(_, is_articulation)| **is_articulation)
+            .map(|(coords, _)| *coords)
+            .collect()
+    }
+
+    fn idx(&self, coords: DungeonCoordinates) -> Option<usize> {
+        self.rooms.iter().position(|c| *c == coords)
+    }
+
+    fn add_floor(&mut self, floor: &FloorLayout) {
+        for room in floor.rooms.iter() {
+            let coords = DungeonCoordinates::new(floor.floor, room.coords.col, room.coords.row);
+            for direction in room.exits.iter() {
+                self.connect(coords, *direction, coords.neighbor(*direction));
+            }
+        }
+    }
+
+    // Adds the connection from one room to another; the way back is added separately
+    fn connect(
+        &mut self,
+        from: DungeonCoordinates,
+        direction: Direction3D,
+        to: DungeonCoordinates,
+    ) {
+        let from_idx = self.add_room(from);
+        let to_idx = self.add_room(to);
+        if !self.edges[from_idx].contains(&(to_idx, direction)) {
+            self.edges[from_idx].push((to_idx, direction));
+        }
+    }
+
+    fn add_room(&mut self, coords: DungeonCoordinates) -> usize {
+        self.idx(coords).unwrap_or_else(|| {
+            self.rooms.push(coords);
+            self.edges.push(vec![]);
+            self.rooms.len() - 1
+        })
+    }
+}
+
+// State of the depth first search for articulation points (Hopcroft and Tarjan)
+struct ArticulationSearch {
+    order: Vec<Option<usize>>,
+    low: Vec<usize>,
+    is_articulation: Vec<bool>,
+    counter: usize,
+}
+
+impl ArticulationSearch {
+    fn visit(&mut self, graph: &RoomGraph, idx: usize, parent: Option<usize>) {
+        self.order[idx] = Some(self.counter);
+        self.low[idx] = self.counter;
+        self.counter += 1;
+
+        let mut children = 0;
+        for (neighbor, _) in graph.edges[idx].iter() {
+            match self.order[*neighbor] {
+                Some(order) => {
+                    if Some(*neighbor) != parent {
+                        self.low[idx] = self.low[idx].min(order);
+                    }
+                }
+                None => {
+                    children += 1;
+                    self.visit(graph, *neighbor, Some(idx));
+                    self.low[idx] = self.low[idx].min(self.low[*neighbor]);
+                    if parent.is_some() && self.low[*neighbor] >= self.order[idx].unwrap() {
+                        self.is_articulation[idx] = true;
+                    }
+                }
+            }
+        }
+
+        if parent.is_none() && children > 1 {
+            self.is_articulation[idx] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::{
+        dungeon::coords::StairCoordinates,
+        floor::floor_architecture::{FloorRoom, RoomCoordinates},
+    };
+
+    use super::*;
+
+    #[test]
+    fn connects_rooms_by_exits_and_stairs() {
+        let sut = RoomGraph::from_layout(&create_layout());
+
+        assert_eq!(5, sut.rooms.len());
+        assert_eq!(
+            vec![
+                (DungeonCoordinates::new(0, 1, 0), Direction3D::Left),
+                (DungeonCoordinates::new(1, 2, 0), Direction3D::Up),
+            ],
+            sut.neighbors(DungeonCoordinates::new(0, 2, 0))
+        );
+        assert_eq!(2, sut.degree(DungeonCoordinates::new(0, 1, 0)));
+        assert_eq!(0, sut.degree(DungeonCoordinates::new(5, 0, 0)));
+    }
+
+    #[test]
+    fn finds_leaves() {
+        let sut = RoomGraph::from_layout(&create_layout());
+
+        assert_eq!(
+            vec![
+                DungeonCoordinates::new(0, 0, 0),
+                DungeonCoordinates::new(1, 2, 1)
+            ],
+            sut.leaves()
+        );
+    }
+
+    #[test]
+    fn finds_shortest_path_across_floors() {
+        let sut = RoomGraph::from_layout(&create_layout());
+
+        let result = sut.shortest_path(
+            DungeonCoordinates::new(0, 0, 0),
+            DungeonCoordinates::new(1, 2, 1),
+        );
+
+        assert_eq!(
+            Some(vec![
+                DungeonCoordinates::new(0, 0, 0),
+                DungeonCoordinates::new(0, 1, 0),
+                DungeonCoordinates::new(0, 2, 0),
+                DungeonCoordinates::new(1, 2, 0),
+                DungeonCoordinates::new(1, 2, 1),
+            ]),
+            result
+        );
+    }
+
+    #[test]
+    fn finds_no_path_between_disconnected_rooms() {
+        let sut = RoomGraph::from_floor(&create_layout().floors[1]);
+
+        let result = sut.shortest_path(
+            DungeonCoordinates::new(1, 2, 0),
+            DungeonCoordinates::new(0, 0, 0),
+        );
+
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn finds_rooms_reachable_without_blocked_connections() {
+        let sut = RoomGraph::from_layout(&create_layout());
+
+        let result = sut.reachable(
+            DungeonCoordinates::new(0, 0, 0),
+            &[(
+                DungeonCoordinates::new(1, 2, 0),
+                DungeonCoordinates::new(0, 2, 0),
+            )],
+        );
+
+        assert_eq!(
+            vec![
+                DungeonCoordinates::new(0, 0, 0),
+                DungeonCoordinates::new(0, 1, 0),
+                DungeonCoordinates::new(0, 2, 0),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn weighs_distances_by_direction() {
+        let sut = RoomGraph::from_layout(&create_layout());
+
+        let result = sut.weighted_distances(DungeonCoordinates::new(0, 0, 0), |d| match d {
+            Direction3D::Up | Direction3D::Down => 5,
+            _ => 1,
+        });
+
+        assert_eq!(
+            vec![
+                (DungeonCoordinates::new(0, 0, 0), 0),
+                (DungeonCoordinates::new(0, 1, 0), 1),
+                (DungeonCoordinates::new(0, 2, 0), 2),
+                (DungeonCoordinates::new(1, 2, 0), 7),
+                (DungeonCoordinates::new(1, 2, 1), 8),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn finds_articulation_points() {
+        let mut layout = create_layout();
+        // close a loop on the first floor: 0/0 - 1/0 - 1/1 - 0/1 - 0/0
+        layout.floors[0].rooms[0].exits.push(Direction3D::Bottom);
+        layout.floors[0].rooms[1].exits.push(Direction3D::Bottom);
+        layout.floors[0].rooms.push(create_room(
+            0,
+            1,
+            vec![Direction3D::Top, Direction3D::Right],
+        ));
+        layout.floors[0]
+            .rooms
+            .push(create_room(1, 1, vec![Direction3D::Top, Direction3D::Left]));
+        let sut = RoomGraph::from_layout(&layout);
+
+        let result = sut.articulation_points();
+
+        assert_eq!(
+            vec![
+                DungeonCoordinates::new(0, 1, 0),
+                DungeonCoordinates::new(0, 2, 0),
+                DungeonCoordinates::new(1, 2, 0),
+            ],
+            result
+        );
+    }
+
+    // floor 0: 0/0 - 1/0 - 2/0, with stairs up from 2/0
+    // floor 1: 2/0 - 2/1
+    fn create_layout() -> DungeonLayout {
+        use Direction3D::*;
+
+        create_dungeon_layout(
+            0,
+            vec![
+                (
+                    0,
+                    vec![
+                        create_room(0, 0, vec![Right]),
+                        create_room(1, 0, vec![Right, Left]),
+                        create_room(2, 0, vec![Left]),
+                    ],
+                ),
+                (
+                    1,
+                    vec![
+                        create_room(2, 0, vec![Bottom]),
+                        create_room(2, 1, vec![Top]),
+                    ],
+                ),
+            ],
+            vec![StairCoordinates::from_coords(0, 2, 0, 1)],
+        )
+    }
+
+    pub(crate) fn create_room(col: i32, row: i32, exits: Vec<Direction3D>) -> FloorRoom {
+        FloorRoom {
+            coords: RoomCoordinates { col, row },
+            exits,
+            ..Default::default()
+        }
+    }
+
+    /// Layout of the given floors and their rooms, all generated from the same seed
+    pub(crate) fn create_dungeon_layout(
+        seed: u64,
+        floors: Vec<(i32, Vec<FloorRoom>)>,
+        stairs: Vec<StairCoordinates>,
+    ) -> DungeonLayout {
+        DungeonLayout {
+            seed,
+            floors: floors
+                .into_iter()
+                .map(|(floor, rooms)| FloorLayout {
+                    rooms,
+                    floor,
+                    dungeon_seed: seed,
+                })
+                .collect(),
+            stairs,
+            ..Default::default()
+        }
+    }
+}
@@ -1,14 +1,31 @@
 use std::ops::Range;
 
-use crate::floor::floor_architecture::FloorLayout;
+use crate::floor::floor_architecture::{FloorConnections, FloorLayout};
 
-use super::coords::{DungeonCoordinates, StairCoordinates};
+use super::{
+    coords::{DungeonCoordinates, StairCoordinates},
+    difficulty::DifficultyConfig,
+    progression::{Progression, ProgressionConfig},
+    role::RoomRoleConfig,
+};
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct DungeonLayoutConfig {
     pub floors_above: Range<u8>,
     pub floors_below: Range<u8>,
     pub floor_size: Range<u8>,
+    /// How the adjacent rooms of each floor are connected
+    pub connections: FloorConnections,
+    pub roles: RoomRoleConfig,
+    /// Locked doors on the way from the first to the last room
+    pub progression: ProgressionConfig,
+    /// Weights of the difficulty of the rooms; the hardest room becomes the last room
+    pub difficulty: DifficultyConfig,
 }
 
 impl Default for DungeonLayoutConfig {
@@ -17,27 +34,39 @@ impl Default for DungeonLayoutConfig {
             floor_size: 3..5,
             floors_above: 0..2,
             floors_below: 0..2,
+            connections: FloorConnections::All,
+            roles: RoomRoleConfig::default(),
+            progression: ProgressionConfig::default(),
+            difficulty: DifficultyConfig::default(),
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DungeonLayout {
+    /// Seed the layout was created from; the seeds of all floors and rooms are derived from it
+    pub seed: u64,
     pub coords: Vec<DungeonCoordinates>,
     pub floors: Vec<FloorLayout>,
     pub stairs: Vec<StairCoordinates>,
     pub first_room: DungeonCoordinates,
     pub last_room: DungeonCoordinates,
+    /// Locks and keys on the way from the first to the last room
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub progression: Progression,
 }
 
 impl Default for DungeonLayout {
     fn default() -> Self {
         Self {
+            seed: 0,
             coords: vec![],
             floors: vec![],
             stairs: vec![],
             first_room: DungeonCoordinates::default(),
             last_room: DungeonCoordinates::default(),
+            progression: Progression::default(),
         }
     }
 }
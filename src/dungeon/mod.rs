@@ -1,6 +1,16 @@
 pub mod coords;
+pub mod difficulty;
 pub mod dungeon_architecture;
 pub mod dungeon_builder;
+pub mod graph;
 pub mod layout;
 pub mod print;
+pub mod progression;
+pub mod role;
 pub mod room;
+#[cfg(feature = "serde")]
+pub mod save;
+pub mod seed;
+pub mod selection;
+pub mod spawn;
+pub mod stitch;
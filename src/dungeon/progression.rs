@@ -0,0 +1,386 @@
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+
+use crate::{
+    direction::Direction3D,
+    error::GenerationError,
+    room::{distance::DistanceMap, pathfinding::walking_cost, tile::DungeonTile},
+};
+
+use super::{
+    coords::DungeonCoordinates,
+    graph::RoomGraph,
+    layout::DungeonLayout,
+    room::ArrangedDungeonRoom,
+    seed::{progression_seed, seeded_rng},
+};
+
+/// Settings for gating the way from the first to the last room with locked doors
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ProgressionConfig {
+    /// Number of locked doors on the critical path; fewer are placed if there are not enough connections to lock
+    pub locks: usize,
+}
+
+/// A locked door on the exit of a room, leading to a neighboring room on the same floor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lock {
+    pub id: usize,
+    /// Room with the locked door, on the side of the first room
+    pub room: DungeonCoordinates,
+    /// Side of the room with the locked door
+    pub direction: Direction3D,
+    /// Room behind the locked door
+    pub target: DungeonCoordinates,
+}
+
+/// The key opening the lock with the same id
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Key {
+    pub lock: usize,
+    pub room: DungeonCoordinates,
+}
+
+/// Locks and keys gating the way through the dungeon.
+/// Locks are ordered along the critical path, and the key of every lock can be reached
+/// from the first room with the keys of all locks before it.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progression {
+    /// Rooms on the shortest way from the first to the last room of the dungeon, across floors
+    pub critical_path: Vec<DungeonCoordinates>,
+    pub locks: Vec<Lock>,
+    pub keys: Vec<Key>,
+}
+
+impl Progression {
+    fn locked_connections(
+        &self,
+        from_lock: usize,
+    ) -> Vec<(DungeonCoordinates, DungeonCoordinates)> {
+        self.locks
+            .iter()
+            .skip(from_lock)
+            .map(|l| (l.room, l.target))
+            .collect()
+    }
+}
+
+/// Chooses the locks on the critical path of the layout and the rooms holding their keys.
+/// Only connections on the critical path which cannot be bypassed are locked; stairs are never locked.
+pub fn plan_progression(layout: &DungeonLayout, config: &ProgressionConfig) -> Progression {
+    let graph = RoomGraph::from_layout(layout);
+    let critical_path = graph
+        .shortest_path(layout.first_room, layout.last_room)
+        .unwrap_or_default();
+    let mut progression = Progression {
+        critical_path: critical_path.clone(),
+        ..Default::default()
+    };
+
+    // connections which disconnect the last room from the first room when locked
+    let mut candidates: Vec<usize> = (1..critical_path.len())
+        .filter(|idx| critical_path[idx - 1].floor == critical_path[*idx].floor)
+        .filter(|idx| {
+            let connection = (critical_path[idx - 1], critical_path[*idx]);
+            !graph
+                .reachable(layout.first_room, &[connection])
+                .contains(&layout.last_room)
+        })
+        .collect();
+
+    let rng = &mut seeded_rng::<Pcg64>(progression_seed(layout.seed));
+    candidates.shuffle(rng);
+    candidates.truncate(config.locks);
+    candidates.sort();
+
+    for (id, path_idx) in candidates.into_iter().enumerate() {
+        let (room, target) = (critical_path[path_idx - 1], critical_path[path_idx]);
+        let direction = graph
+            .neighbors(room)
+            .into_iter()
+            .find(|(neighbor, _)| *neighbor == target)
+            .map(|(_, direction)| direction)
+            .unwrap_or(Direction3D::None);
+
+        progression.locks.push(Lock {
+            id,
+            room,
+            direction,
+            target,
+        });
+    }
+
+    for lock in 0..progression.locks.len() {
+        let reachable = graph.reachable(layout.first_room, &progression.locked_connections(lock));
+
+        // prefer dead ends away from the first room, which do not hold a key yet
+        let free: Vec<DungeonCoordinates> = reachable
+            .into_iter()
+            .filter(|c| *c != layout.first_room && !progression.keys.iter().any(|k| k.room == *c))
+            .collect();
+        let dead_ends: Vec<DungeonCoordinates> = free
+            .iter()
+            .filter(|c| graph.degree(**c) == 1)
+            .copied()
+            .collect();
+        let candidates = if dead_ends.is_empty() {
+            free
+        } else {
+            dead_ends
+        };
+
+        progression.keys.push(Key {
+            lock,
+            room: candidates.choose(rng).copied().unwrap_or(layout.first_room),
+        });
+    }
+
+    progression
+}
+
+/// Places the locked doors and keys of the progression on the tiles of the rooms.
+/// Locked doors replace the exit tiles on the locked side of a room.
+/// Keys are placed on the floor tile farthest away from the exits and stairs of their room.
+pub fn place_locks_and_keys(
+    rooms: &mut [ArrangedDungeonRoom],
+    progression: &Progression,
+) -> Result<(), GenerationError> {
+    for lock in progression.locks.iter() {
+        let room = find_room(rooms, lock.room)?;
+        for idx in room.exit_tiles(lock.direction) {
+            room.tiles[idx] = DungeonTile::LockedDoor;
+        }
+    }
+
+    for key in progression.keys.iter() {
+        let room = find_room(rooms, key.room)?;
+        let mut sources: Vec<usize> = room
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| {
+                matches!(
+                    t,
+                    DungeonTile::Exit
+                        | DungeonTile::LockedDoor
+                        | DungeonTile::StairsUp
+                        | DungeonTile::StairsDown
+                )
+            })
+            .map(|(idx, _)| idx)
+            .chain(room.exits.iter().map(|(idx, _)| *idx))
+            .chain(room.entry.map(|(idx, _)| idx))
+            .collect();
+        if sources.is_empty() {
+            sources.extend(room.pathing.first());
+        }
+
+        let distances = DistanceMap::new(room, &sources, walking_cost);
+        let key_tile = distances
+            .farthest_where(|idx| room.tiles[idx] == DungeonTile::Floor)
+            .ok_or(GenerationError::NoWalkableTiles)?;
+        room.tiles[key_tile] = DungeonTile::Key;
+    }
+
+    Ok(())
+}
+
+fn find_room(
+    rooms: &mut [ArrangedDungeonRoom],
+    coords: DungeonCoordinates,
+) -> Result<&mut ArrangedDungeonRoom, GenerationError> {
+    rooms
+        .iter_mut()
+        .find(|r| r.dungeon_coords == coords)
+        .ok_or(GenerationError::RoomNotFound(coords))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dungeon::{
+            coords::StairCoordinates,
+            graph::test::{create_dungeon_layout, create_room},
+        },
+        room::print::{parse_room, print_room},
+    };
+
+    use super::*;
+
+    #[test]
+    fn locks_connections_on_critical_path() {
+        let layout = create_layout();
+
+        let result = plan_progression(&layout, &ProgressionConfig { locks: 5 });
+
+        assert_eq!(5, result.critical_path.len());
+        // the stairs and the loop around 1/1 on the first floor can not be locked
+        assert_eq!(
+            vec![
+                Lock {
+                    id: 0,
+                    room: DungeonCoordinates::new(0, 0, 0),
+                    direction: Direction3D::Right,
+                    target: DungeonCoordinates::new(0, 1, 0),
+                },
+                Lock {
+                    id: 1,
+                    room: DungeonCoordinates::new(1, 2, 0),
+                    direction: Direction3D::Bottom,
+                    target: DungeonCoordinates::new(1, 2, 1),
+                },
+            ],
+            result.locks
+        );
+    }
+
+    #[test]
+    fn places_keys_before_their_locks() {
+        let layout = create_layout();
+        let graph = RoomGraph::from_layout(&layout);
+
+        let result = plan_progression(&layout, &ProgressionConfig { locks: 2 });
+
+        assert_eq!(2, result.keys.len());
+        for (lock, key) in result.keys.iter().enumerate() {
+            assert_eq!(lock, key.lock);
+            let reachable = graph.reachable(layout.first_room, &result.locked_connections(lock));
+            assert!(reachable.contains(&key.room));
+        }
+        // the first key can only be in the first room, the second one is put into the dead end
+        assert_eq!(DungeonCoordinates::new(0, 0, 0), result.keys[0].room);
+        assert_eq!(DungeonCoordinates::new(0, 1, -1), result.keys[1].room);
+    }
+
+    #[test]
+    fn places_no_locks_without_config() {
+        let result = plan_progression(&create_layout(), &ProgressionConfig::default());
+
+        assert!(result.locks.is_empty());
+        assert!(result.keys.is_empty());
+    }
+
+    #[test]
+    fn places_locked_doors_and_keys_on_tiles() {
+        let mut rooms = vec![
+            create_arranged_room(
+                DungeonCoordinates::new(0, 0, 0),
+                "#####\n#...#\n#...E\n#####",
+            ),
+            create_arranged_room(DungeonCoordinates::new(0, 1, 0), "###\nE.#\n###"),
+        ];
+        let progression = Progression {
+            locks: vec![Lock {
+                id: 0,
+                room: DungeonCoordinates::new(0, 0, 0),
+                direction: Direction3D::Right,
+                target: DungeonCoordinates::new(0, 1, 0),
+            }],
+            keys: vec![Key {
+                lock: 0,
+                room: DungeonCoordinates::new(0, 0, 0),
+            }],
+            ..Default::default()
+        };
+
+        place_locks_and_keys(&mut rooms, &progression).unwrap();
+
+        assert_eq!(
+            "#####\n#k..#\n#...L\n#####",
+            print_room(
+                rooms[0].rows,
+                rooms[0].columns,
+                rooms[0].tiles.clone(),
+                0,
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn places_keys_far_from_exits_past_doors_and_bridges() {
+        let mut rooms = vec![create_arranged_room(
+            DungeonCoordinates::new(0, 0, 0),
+            "########\n#..+...#\n#=.....E\n########",
+        )];
+        let progression = Progression {
+            keys: vec![Key {
+                lock: 0,
+                room: DungeonCoordinates::new(0, 0, 0),
+            }],
+            ..Default::default()
+        };
+
+        place_locks_and_keys(&mut rooms, &progression).unwrap();
+
+        assert_eq!(DungeonTile::Key, rooms[0].tiles[9]);
+    }
+
+    #[test]
+    fn fails_to_place_keys_in_missing_rooms() {
+        let mut rooms = vec![];
+        let progression = Progression {
+            keys: vec![Key {
+                lock: 0,
+                room: DungeonCoordinates::new(0, 0, 0),
+            }],
+            ..Default::default()
+        };
+
+        let result = place_locks_and_keys(&mut rooms, &progression);
+
+        assert!(matches!(result, Err(GenerationError::RoomNotFound(_))));
+    }
+
+    fn create_arranged_room(coords: DungeonCoordinates, tiles: &str) -> ArrangedDungeonRoom {
+        let mut room = parse_room(tiles).unwrap();
+        room.pathing();
+        let mut arranged = ArrangedDungeonRoom::from(&room);
+        arranged.list_border_exits();
+        arranged.dungeon_coords = coords;
+        arranged
+    }
+
+    // floor 0: 0/0 - 1/0 - 2/0, where 1/0 and 2/0 are also connected through 1/1 and 2/1, and a dead end at 1/-1
+    // floor 1: 2/0 - 2/1, with stairs down from 2/0
+    fn create_layout() -> DungeonLayout {
+        use Direction3D::*;
+
+        DungeonLayout {
+            first_room: DungeonCoordinates::new(0, 0, 0),
+            last_room: DungeonCoordinates::new(1, 2, 1),
+            ..create_dungeon_layout(
+                1,
+                vec![
+                    (
+                        0,
+                        vec![
+                            create_room(0, 0, vec![Right]),
+                            create_room(1, 0, vec![Right, Left, Top, Bottom]),
+                            create_room(2, 0, vec![Left, Bottom]),
+                            create_room(1, 1, vec![Right, Top]),
+                            create_room(2, 1, vec![Left, Top]),
+                            create_room(1, -1, vec![Bottom]),
+                        ],
+                    ),
+                    (
+                        1,
+                        vec![
+                            create_room(2, 0, vec![Bottom]),
+                            create_room(2, 1, vec![Top]),
+                        ],
+                    ),
+                ],
+                vec![StairCoordinates::from_coords(0, 2, 0, 1)],
+            )
+        }
+    }
+}
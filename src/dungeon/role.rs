@@ -0,0 +1,199 @@
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+
+use super::{
+    coords::DungeonCoordinates,
+    graph::RoomGraph,
+    layout::DungeonLayout,
+    seed::{role_seed, seeded_rng},
+};
+
+/// Semantic role of a room within the dungeon, e.g. to choose a fitting room builder or to place content
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RoomRole {
+    #[default]
+    Normal,
+    /// The first room of the dungeon
+    Start,
+    /// The last room of the dungeon, farthest away from the start
+    Boss,
+    Treasure,
+    Shop,
+    /// A room with a single connection, which got no other role
+    DeadEnd,
+    /// A room with many connections
+    Hub,
+}
+
+/// Rules for assigning roles to the rooms of a dungeon layout.
+/// The first room is the start and the last room is the boss room. Of the other rooms,
+/// dead ends get a role from dead_end_weights, rooms with at least hub_degree connections become hubs,
+/// and all remaining rooms get a role from room_weights.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RoomRoleConfig {
+    /// Rooms with at least this many connections, including stairs, become hubs
+    pub hub_degree: usize,
+    /// Relative weights of the roles of dead ends
+    pub dead_end_weights: Vec<(RoomRole, u32)>,
+    /// Relative weights of the roles of all other rooms
+    pub room_weights: Vec<(RoomRole, u32)>,
+}
+
+impl Default for RoomRoleConfig {
+    fn default() -> Self {
+        Self {
+            hub_degree: 4,
+            dead_end_weights: vec![(RoomRole::Treasure, 1), (RoomRole::DeadEnd, 1)],
+            room_weights: vec![(RoomRole::Normal, 9), (RoomRole::Shop, 1)],
+        }
+    }
+}
+
+/// Sets the role of every room of the layout according to the rules of the config.
+/// Every room draws its role with its own seed derived from the dungeon seed.
+pub fn assign_roles(layout: &mut DungeonLayout, config: &RoomRoleConfig) {
+    let graph = RoomGraph::from_layout(layout);
+    let (seed, first_room, last_room) = (layout.seed, layout.first_room, layout.last_room);
+
+    for floor in layout.floors.iter_mut() {
+        for room in floor.rooms.iter_mut() {
+            let coords = DungeonCoordinates::new(floor.floor, room.coords.col, room.coords.row);
+            let degree = graph.degree(coords);
+            let weights = if degree <= 1 {
+                &config.dead_end_weights
+            } else {
+                &config.room_weights
+            };
+
+            room.role = if coords == first_room {
+                RoomRole::Start
+            } else if coords == last_room {
+                RoomRole::Boss
+            } else if degree > 1 && degree >= config.hub_degree {
+                RoomRole::Hub
+            } else {
+                weights
+                    .choose_weighted(
+                        &mut seeded_rng::<Pcg64>(role_seed(seed, coords)),
+                        |(_, w)| *w,
+                    )
+                    .map(|(role, _)| *role)
+                    .unwrap_or_default()
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        direction::Direction3D,
+        dungeon::{
+            coords::StairCoordinates,
+            graph::test::{create_dungeon_layout, create_room},
+        },
+        floor::floor_architecture::RoomCoordinates,
+    };
+
+    use super::*;
+
+    #[test]
+    fn assigns_start_and_boss_rooms() {
+        let mut layout = create_layout();
+
+        assign_roles(&mut layout, &RoomRoleConfig::default());
+
+        assert_eq!(RoomRole::Start, role(&layout, 0, 1, 0));
+        assert_eq!(RoomRole::Boss, role(&layout, 1, 1, 1));
+    }
+
+    #[test]
+    fn assigns_roles_to_dead_ends_and_hubs() {
+        let mut layout = create_layout();
+        let config = RoomRoleConfig {
+            hub_degree: 3,
+            dead_end_weights: vec![(RoomRole::Treasure, 1)],
+            room_weights: vec![(RoomRole::Shop, 1)],
+        };
+
+        assign_roles(&mut layout, &config);
+
+        // the center room has 4 exits, plus stairs
+        assert_eq!(RoomRole::Hub, role(&layout, 0, 1, 1));
+        assert_eq!(RoomRole::Treasure, role(&layout, 0, 0, 1));
+        assert_eq!(RoomRole::Treasure, role(&layout, 0, 2, 1));
+        assert_eq!(RoomRole::Treasure, role(&layout, 0, 1, 2));
+    }
+
+    #[test]
+    fn assigns_rooms_without_weights_the_normal_role() {
+        let mut layout = create_layout();
+        let config = RoomRoleConfig {
+            dead_end_weights: vec![],
+            ..Default::default()
+        };
+
+        assign_roles(&mut layout, &config);
+
+        assert_eq!(RoomRole::Normal, role(&layout, 0, 0, 1));
+    }
+
+    #[test]
+    fn assigns_same_roles_for_same_seed() {
+        let mut first = create_layout();
+        let mut second = create_layout();
+
+        assign_roles(&mut first, &RoomRoleConfig::default());
+        assign_roles(&mut second, &RoomRoleConfig::default());
+
+        assert_eq!(first, second);
+    }
+
+    fn role(layout: &DungeonLayout, floor: i32, col: i32, row: i32) -> RoomRole {
+        layout
+            .floors
+            .iter()
+            .find(|f| f.floor == floor)
+            .and_then(|f| {
+                f.rooms
+                    .iter()
+                    .find(|r| r.coords == RoomCoordinates { col, row })
+            })
+            .unwrap()
+            .role
+    }
+
+    // floor 0: a cross of rooms around 1/1, the start room at 1/0
+    // floor 1: the boss room above 1/1
+    fn create_layout() -> DungeonLayout {
+        use Direction3D::*;
+
+        DungeonLayout {
+            first_room: DungeonCoordinates::new(0, 1, 0),
+            last_room: DungeonCoordinates::new(1, 1, 1),
+            ..create_dungeon_layout(
+                3,
+                vec![
+                    (
+                        0,
+                        vec![
+                            create_room(1, 0, vec![Bottom]),
+                            create_room(1, 1, vec![Right, Left, Top, Bottom]),
+                            create_room(0, 1, vec![Right]),
+                            create_room(2, 1, vec![Left]),
+                            create_room(1, 2, vec![Top]),
+                        ],
+                    ),
+                    (1, vec![create_room(1, 1, vec![])]),
+                ],
+                vec![StairCoordinates::from_coords(1, 1, 0, 1)],
+            )
+        }
+    }
+}
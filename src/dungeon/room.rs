@@ -1,11 +1,17 @@
 use crate::{
     direction::Direction3D,
-    room::{room::DungeonRoom, tile::DungeonTile},
+    room::{
+        attributes::TileAttributes,
+        room::DungeonRoom,
+        tile::{DungeonTile, TileGrid},
+        transform::{mirror_idx, mirror_tiles, rotate_idx, rotate_tiles},
+    },
 };
 
-use super::coords::DungeonCoordinates;
+use super::{coords::DungeonCoordinates, role::RoomRole};
 
 #[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrangedDungeonRoom {
     pub tiles: Vec<DungeonTile>,
     pub pathing: Vec<usize>,
@@ -14,9 +20,20 @@ pub struct ArrangedDungeonRoom {
     pub dungeon_coords: DungeonCoordinates,
     pub entry: Option<(usize, Direction3D)>,
     pub exits: Vec<(usize, Direction3D)>,
+    /// Quarter turns clockwise applied to the room, from 0 to 3
     pub rotation: i8,
+    /// Whether the room was mirrored along its vertical axis, before it was turned by rotation
+    pub mirrored: bool,
     pub stair_up: bool,
     pub stair_down: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub role: RoomRole,
+    /// Difficulty score of the room, see DifficultyConfig
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub difficulty: u32,
+    /// Game specific values on the tiles, turned and mirrored along with the tiles
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: TileAttributes,
 }
 
 impl Default for ArrangedDungeonRoom {
@@ -30,12 +47,30 @@ impl Default for ArrangedDungeonRoom {
             entry: None,
             exits: vec![],
             rotation: 0,
+            mirrored: false,
             stair_up: false,
             stair_down: false,
+            role: RoomRole::Normal,
+            difficulty: 0,
+            attributes: TileAttributes::default(),
         }
     }
 }
 
+impl TileGrid for ArrangedDungeonRoom {
+    fn grid_tiles(&self) -> &[DungeonTile] {
+        &self.tiles
+    }
+
+    fn grid_rows(&self) -> usize {
+        self.rows
+    }
+
+    fn grid_columns(&self) -> usize {
+        self.columns
+    }
+}
+
 impl ArrangedDungeonRoom {
     pub fn from(room: &DungeonRoom) -> Self {
         Self {
@@ -47,8 +82,12 @@ impl ArrangedDungeonRoom {
             exits: vec![],
             dungeon_coords: DungeonCoordinates::default(),
             rotation: 0,
+            mirrored: false,
             stair_down: room.stair_down,
             stair_up: room.stair_up,
+            role: RoomRole::Normal,
+            difficulty: 0,
+            attributes: room.attributes.clone(),
         }
     }
 
@@ -76,6 +115,111 @@ impl ArrangedDungeonRoom {
         self.pathing.iter().filter(filter).map(|t| *t).collect()
     }
 
+    /// Exit tiles on the given side of the room, as listed in exits.
+    /// Exits turned into doors stay listed.
+    pub fn exit_tiles(&self, direction: Direction3D) -> Vec<usize> {
+        self.exits
+            .iter()
+            .filter(|(_, d)| *d == direction)
+            .map(|(idx, _)| *idx)
+            .collect()
+    }
+
+    /// Lists the exit tiles and doors on the border of the room in exits, with the side they are on.
+    /// Used for exits placed by room builders; a tile in a corner is listed for both of its sides.
+    pub fn list_border_exits(&mut self) {
+        let sides = [
+            Direction3D::Top,
+            Direction3D::Bottom,
+            Direction3D::Left,
+            Direction3D::Right,
+        ];
+
+        let mut exits = vec![];
+        for (idx, tile) in self.tiles.iter().enumerate() {
+            if *tile != DungeonTile::Exit && !tile.is_door() {
+                continue;
+            }
+
+            for direction in sides {
+                let on_side = match direction {
+                    Direction3D::Top => self.top(idx),
+                    Direction3D::Bottom => self.bottom(idx),
+                    Direction3D::Left => self.left(idx),
+                    _ => self.right(idx),
+                };
+                if on_side && !self.exits.contains(&(idx, direction)) {
+                    exits.push((idx, direction));
+                }
+            }
+        }
+
+        self.exits.append(&mut exits);
+    }
+
+    /// Turns the room by the given quarter turns, clockwise for positive and counter-clockwise for negative values.
+    /// Tiles, pathing, entry and exits are remapped, including the directions of entry and exits.
+    pub fn rotate(&mut self, quarter_turns: i8) {
+        let turns = quarter_turns.rem_euclid(4);
+        for _ in 0..turns {
+            let (rows, columns) = (self.rows, self.columns);
+            self.transform(
+                |idx| rotate_idx(rows, columns, idx),
+                |d| d.rotate_clockwise(),
+            );
+            self.tiles = rotate_tiles(rows, columns, &self.tiles);
+            self.rows = columns;
+            self.columns = rows;
+        }
+
+        self.rotation = (self.rotation + turns).rem_euclid(4);
+    }
+
+    /// Mirrors the room along its vertical axis, swapping its left and right side
+    pub fn mirror_horizontally(&mut self) {
+        let columns = self.columns;
+        self.transform(|idx| mirror_idx(columns, idx), |d| d.mirror_horizontally());
+        self.tiles = mirror_tiles(columns, &self.tiles);
+
+        // mirroring a turned room equals mirroring first and turning into the opposite direction
+        self.rotation = (-self.rotation).rem_euclid(4);
+        self.mirrored = !self.mirrored;
+    }
+
+    /// Mirrors the room along its horizontal axis, swapping its top and bottom side
+    pub fn mirror_vertically(&mut self) {
+        self.rotate(2);
+        self.mirror_horizontally();
+    }
+
+    /// Turns all exit tiles of the room into doors.
+    /// Exits stay listed in exits, so exit_tiles still finds them.
+    pub fn set_exit_doors(&mut self) {
+        for tile in self.tiles.iter_mut() {
+            if *tile == DungeonTile::Exit {
+                *tile = DungeonTile::Door;
+            }
+        }
+    }
+
+    fn transform<I, D>(&mut self, idx_mapping: I, direction_mapping: D)
+    where
+        I: Fn(usize) -> usize,
+        D: Fn(&Direction3D) -> Direction3D,
+    {
+        self.pathing = self.pathing.iter().map(|idx| idx_mapping(*idx)).collect();
+        self.pathing.sort();
+        self.exits = self
+            .exits
+            .iter()
+            .map(|(idx, d)| (idx_mapping(*idx), direction_mapping(d)))
+            .collect();
+        self.entry = self
+            .entry
+            .map(|(idx, d)| (idx_mapping(idx), direction_mapping(&d)));
+        self.attributes = self.attributes.map_indices(idx_mapping);
+    }
+
     fn top(&self, tile: usize) -> bool {
         self.row(tile) == 0
     }
@@ -95,6 +239,8 @@ impl ArrangedDungeonRoom {
 
 #[cfg(test)]
 mod test {
+    use crate::room::attributes::AttributeValue;
+
     use super::*;
 
     #[test]
@@ -113,4 +259,169 @@ mod test {
         assert_eq!(vec![0, 1], down);
         assert_eq!(vec![0, 3], left);
     }
+
+    #[test]
+    fn lists_exit_tiles_on_border() {
+        let mut sut = create_corner_room();
+        sut.exits = vec![];
+
+        sut.list_border_exits();
+
+        assert_eq!(
+            vec![(0, Direction3D::Top), (0, Direction3D::Left)],
+            sut.exits
+        );
+        assert_eq!(vec![0], sut.exit_tiles(Direction3D::Top));
+        assert!(sut.exit_tiles(Direction3D::Bottom).is_empty());
+    }
+
+    #[test]
+    fn finds_only_listed_exit_tiles() {
+        let sut = create_corner_room();
+
+        assert_eq!(vec![0], sut.exit_tiles(Direction3D::Top));
+        assert!(sut.exit_tiles(Direction3D::Left).is_empty());
+    }
+
+    #[test]
+    fn keeps_exit_doors_listed() {
+        let mut sut = create_corner_room();
+
+        sut.set_exit_doors();
+
+        assert_eq!(DungeonTile::Door, sut.tiles[0]);
+        assert_eq!(vec![0], sut.exit_tiles(Direction3D::Top));
+    }
+
+    #[test]
+    fn rotates_room_clockwise() {
+        let mut sut = create_corner_room();
+
+        sut.rotate(1);
+
+        assert_eq!(3, sut.rows);
+        assert_eq!(2, sut.columns);
+        assert_eq!(
+            vec![
+                DungeonTile::Wall,
+                DungeonTile::Exit,
+                DungeonTile::Floor,
+                DungeonTile::Floor,
+                DungeonTile::Wall,
+                DungeonTile::Floor,
+            ],
+            sut.tiles
+        );
+        assert_eq!(vec![(1, Direction3D::Right)], sut.exits);
+        assert_eq!(Some((1, Direction3D::Right)), sut.entry);
+        assert_eq!(vec![1, 2, 3, 5], sut.pathing);
+        assert_eq!(1, sut.rotation);
+    }
+
+    #[test]
+    fn turns_and_mirrors_attributes_with_tiles() {
+        let mut sut = create_corner_room();
+        sut.attributes.set("light", 0, 5);
+
+        sut.rotate(1);
+        let rotated = sut.attributes.get("light", 1).cloned();
+        sut.mirror_horizontally();
+
+        assert_eq!(Some(AttributeValue::Number(5)), rotated);
+        assert_eq!(
+            Some(&AttributeValue::Number(5)),
+            sut.attributes.get("light", 0)
+        );
+        assert_eq!(1, sut.attributes.layer("light").len());
+    }
+
+    #[test]
+    fn rotates_room_counter_clockwise() {
+        let mut sut = create_corner_room();
+
+        sut.rotate(-1);
+
+        assert_eq!(vec![(4, Direction3D::Left)], sut.exits);
+        assert_eq!(DungeonTile::Exit, sut.tiles[4]);
+        assert_eq!(3, sut.rotation);
+    }
+
+    #[test]
+    fn full_rotation_restores_room() {
+        let mut sut = create_corner_room();
+
+        sut.rotate(2);
+        sut.rotate(2);
+
+        assert!(create_corner_room() == sut);
+    }
+
+    #[test]
+    fn mirrors_room_horizontally() {
+        let mut sut = create_corner_room();
+
+        sut.mirror_horizontally();
+
+        assert_eq!(
+            vec![
+                DungeonTile::Floor,
+                DungeonTile::Floor,
+                DungeonTile::Exit,
+                DungeonTile::Wall,
+                DungeonTile::Floor,
+                DungeonTile::Wall,
+            ],
+            sut.tiles
+        );
+        assert_eq!(vec![(2, Direction3D::Top)], sut.exits);
+        assert_eq!(vec![0, 1, 2, 4], sut.pathing);
+        assert!(sut.mirrored);
+    }
+
+    #[test]
+    fn mirrors_room_vertically() {
+        let mut sut = create_corner_room();
+
+        sut.mirror_vertically();
+
+        assert_eq!(vec![(3, Direction3D::Bottom)], sut.exits);
+        assert_eq!(DungeonTile::Exit, sut.tiles[3]);
+        assert_eq!(2, sut.rotation);
+        assert!(sut.mirrored);
+    }
+
+    #[test]
+    fn keeps_track_of_rotation_and_mirroring() {
+        let mut sut = create_corner_room();
+
+        sut.rotate(1);
+        sut.mirror_horizontally();
+
+        // the same orientation reached by mirroring first, then turning
+        let mut expected = create_corner_room();
+        expected.mirror_horizontally();
+        expected.rotate(sut.rotation);
+        assert!(expected == sut);
+    }
+
+    // E..
+    // #.#
+    fn create_corner_room() -> ArrangedDungeonRoom {
+        ArrangedDungeonRoom {
+            columns: 3,
+            rows: 2,
+            tiles: vec![
+                DungeonTile::Exit,
+                DungeonTile::Floor,
+                DungeonTile::Floor,
+                DungeonTile::Wall,
+                DungeonTile::Floor,
+                DungeonTile::Wall,
+            ],
+            pathing: vec![0, 1, 2, 4],
+            exits: vec![(0, Direction3D::Top)],
+            entry: Some((0, Direction3D::Top)),
+            ..Default::default()
+        }
+    }
 }
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use super::{layout::DungeonLayout, room::ArrangedDungeonRoom};
+
+/// Version of the save document format written by this crate.
+/// Increased whenever the serialized form of the dungeon changes incompatibly.
+pub const SAVE_VERSION: u32 = 1;
+
+/// A generated dungeon, ready to be cached to disk or sent to a game client in any serde format (e.g. JSON or RON).
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct DungeonSave {
+    /// Format version the document was written with
+    pub version: u32,
+    pub layout: DungeonLayout,
+    pub rooms: Vec<ArrangedDungeonRoom>,
+}
+
+impl DungeonSave {
+    pub fn new(layout: DungeonLayout, rooms: Vec<ArrangedDungeonRoom>) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            layout,
+            rooms,
+        }
+    }
+
+    /// Whether the document was written in a format version this crate can read
+    pub fn is_supported(&self) -> bool {
+        self.version == SAVE_VERSION
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    use crate::{
+        dungeon::{dungeon_architecture::DungeonArchitect, layout::DungeonLayoutConfig},
+        room::{grid::GridRoomBuilder, room_builder::RoomBuilder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn restores_dungeon_from_json() {
+        let save = create_save();
+
+        let json = serde_json::to_string(&save).unwrap();
+        let result: DungeonSave = serde_json::from_str(&json).unwrap();
+
+        assert!(result == save);
+        assert!(result.is_supported());
+    }
+
+    #[test]
+    fn restores_dungeon_from_ron() {
+        let save = create_save();
+
+        let ron = ron::to_string(&save).unwrap();
+        let result: DungeonSave = ron::from_str(&ron).unwrap();
+
+        assert!(result == save);
+    }
+
+    #[test]
+    fn writes_version_into_document() {
+        let json = serde_json::to_value(create_save()).unwrap();
+
+        assert_eq!(Some(SAVE_VERSION as u64), json["version"].as_u64());
+    }
+
+    #[test]
+    fn does_not_support_other_versions() {
+        let mut save = create_save();
+        save.version = SAVE_VERSION + 1;
+
+        assert!(!save.is_supported());
+    }
+
+    fn create_save() -> DungeonSave {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let architect = DungeonArchitect {
+            config: DungeonLayoutConfig::default(),
+        };
+        let layout = architect.create_dungeon_layout(&mut rng);
+        let mut rooms = vec![];
+        for floor in layout.floors.iter() {
+            for room_config in floor.rooms.iter() {
+                let room = GridRoomBuilder::default().create_room(&mut rng, room_config);
+                rooms.push(ArrangedDungeonRoom::from(&room));
+            }
+        }
+        rooms[0].attributes.set("biome", 4, "cave");
+        rooms[0].attributes.set("spawn", 5, true);
+
+        DungeonSave::new(layout, rooms)
+    }
+}
@@ -0,0 +1,97 @@
+use rand::SeedableRng;
+
+use super::coords::DungeonCoordinates;
+
+// distinguishes the kinds of derived seeds, so e.g. floor 0 and the room at 0/0/0 do not share a seed
+const FLOOR_STREAM: u64 = 1;
+const ROOM_STREAM: u64 = 2;
+const ROLE_STREAM: u64 = 3;
+const PROGRESSION_STREAM: u64 = 4;
+const SPAWN_STREAM: u64 = 5;
+
+/// Seed of the layout of a floor, derived from the dungeon seed and the floor number
+pub fn floor_seed(dungeon_seed: u64, floor: i32) -> u64 {
+    derive_seed(dungeon_seed, &[FLOOR_STREAM, floor as u64])
+}
+
+/// Seed of a room, derived from the dungeon seed and the coordinates of the room.
+/// Every room gets its own seed, so changing one room leaves all other rooms unchanged.
+pub fn room_seed(dungeon_seed: u64, coords: DungeonCoordinates) -> u64 {
+    derive_seed(dungeon_seed, &coords_values(ROOM_STREAM, coords))
+}
+
+/// Seed for choosing the role of a room, derived from the dungeon seed and the coordinates of the room
+pub fn role_seed(dungeon_seed: u64, coords: DungeonCoordinates) -> u64 {
+    derive_seed(dungeon_seed, &coords_values(ROLE_STREAM, coords))
+}
+
+/// Seed for placing the locks and keys of the dungeon, derived from the dungeon seed
+pub fn progression_seed(dungeon_seed: u64) -> u64 {
+    derive_seed(dungeon_seed, &[PROGRESSION_STREAM])
+}
+
+/// Seed for placing the spawn points of a room, derived from the dungeon seed and the coordinates of the room
+pub fn spawn_seed(dungeon_seed: u64, coords: DungeonCoordinates) -> u64 {
+    derive_seed(dungeon_seed, &coords_values(SPAWN_STREAM, coords))
+}
+
+/// Random number generator of type R for a derived floor or room seed.
+/// The crate uses Pcg64 by default; dungeon builders can choose another generator, see DungeonBuilder.
+pub fn seeded_rng<R: SeedableRng>(seed: u64) -> R {
+    R::seed_from_u64(seed)
+}
+
+fn coords_values(stream: u64, coords: DungeonCoordinates) -> [u64; 4] {
+    [
+        stream,
+        coords.floor as u64,
+        coords.col as u64,
+        coords.row as u64,
+    ]
+}
+
+fn derive_seed(dungeon_seed: u64, values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(dungeon_seed, |seed, value| mix(seed ^ mix(*value)))
+}
+
+// splitmix64 finalizer, spreading every input bit over the whole seed
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn derives_same_seed_for_same_coordinates() {
+        let coords = DungeonCoordinates::new(1, -2, 3);
+
+        assert_eq!(room_seed(42, coords), room_seed(42, coords));
+        assert_eq!(floor_seed(42, -1), floor_seed(42, -1));
+    }
+
+    #[test]
+    fn derives_distinct_seeds_for_distinct_rooms() {
+        let seeds = [
+            room_seed(42, DungeonCoordinates::new(0, 0, 0)),
+            room_seed(42, DungeonCoordinates::new(0, 0, 1)),
+            room_seed(42, DungeonCoordinates::new(0, 1, 0)),
+            room_seed(42, DungeonCoordinates::new(1, 0, 0)),
+            room_seed(43, DungeonCoordinates::new(0, 0, 0)),
+            floor_seed(42, 0),
+            role_seed(42, DungeonCoordinates::new(0, 0, 0)),
+            progression_seed(42),
+            spawn_seed(42, DungeonCoordinates::new(0, 0, 0)),
+        ];
+
+        for (idx, seed) in seeds.iter().enumerate() {
+            assert!(!seeds[idx + 1..].contains(seed));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::ops::Range;
+
+use rand::{seq::SliceRandom, RngCore};
+
+use crate::{
+    direction::Direction3D,
+    floor::floor_architecture::{FloorLayout, FloorRoom},
+};
+
+use super::coords::DungeonCoordinates;
+
+/// Policy for choosing the room builder of every room of a floor
+pub trait BuilderSelection {
+    /// Index of the room builder for the room, or None if no room builder fits the room.
+    /// Rooms of the floor created before are passed with the indices of their room builders.
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        floor_layout: &FloorLayout,
+        room: &FloorRoom,
+        created: &[(DungeonCoordinates, usize)],
+    ) -> Option<usize>;
+}
+
+/// Condition on a room of the floor layout
+pub type RoomCondition = Box<dyn Fn(&FloorRoom) -> bool>;
+
+/// Makes a room builder available for the rooms matching the rule, with a relative weight
+pub struct BuilderRule {
+    /// Index of the room builder
+    pub builder: usize,
+    /// Relative weight among all rules matching a room
+    pub weight: u32,
+    /// Floors the rule applies to, or all floors if None
+    pub floors: Option<Range<i32>>,
+    /// Condition for the rooms the rule applies to, e.g. on their exits, stairs, coordinates or role
+    pub condition: Option<RoomCondition>,
+}
+
+impl Default for BuilderRule {
+    fn default() -> Self {
+        Self {
+            builder: 0,
+            weight: 1,
+            floors: None,
+            condition: None,
+        }
+    }
+}
+
+impl BuilderRule {
+    pub fn new(builder: usize, weight: u32) -> Self {
+        Self {
+            builder,
+            weight,
+            ..Default::default()
+        }
+    }
+
+    fn matches(&self, floor: i32, room: &FloorRoom) -> bool {
+        self.weight > 0
+            && self.floors.as_ref().is_none_or(|f| f.contains(&floor))
+            && self.condition.as_ref().is_none_or(|c| c(room))
+    }
+}
+
+/// Chooses among the room builders of all rules matching a room, randomly by their weights
+#[derive(Default)]
+pub struct RuleSelection {
+    pub rules: Vec<BuilderRule>,
+    /// Avoids using the same room builder for neighboring rooms, as long as another room builder matches the room
+    pub distinct_neighbors: bool,
+}
+
+impl BuilderSelection for RuleSelection {
+    fn select(
+        &self,
+        rng: &mut dyn RngCore,
+        floor_layout: &FloorLayout,
+        room: &FloorRoom,
+        created: &[(DungeonCoordinates, usize)],
+    ) -> Option<usize> {
+        let matching: Vec<&BuilderRule> = self
+            .rules
+            .iter()
+            .filter(|r| r.matches(floor_layout.floor, room))
+            .collect();
+
+        let mut candidates = matching.clone();
+        if self.distinct_neighbors {
+            let coords =
+                DungeonCoordinates::new(floor_layout.floor, room.coords.col, room.coords.row);
+            let neighbor_builders: Vec<usize> = [
+                Direction3D::Right,
+                Direction3D::Left,
+                Direction3D::Top,
+                Direction3D::Bottom,
+            ]
+            .iter()
+            .filter_map(|d| {
+                created
+                    .iter()
+                    .find(|(c, _)| *c == coords.neighbor(*d))
+                    .map(|(_, builder)| *builder)
+            })
+            .collect();
+
+            candidates.retain(|r| !neighbor_builders.contains(&r.builder));
+            if candidates.is_empty() {
+                candidates = matching;
+            }
+        }
+
+        candidates
+            .choose_weighted(rng, |r| r.weight)
+            .ok()
+            .map(|r| r.builder)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    use crate::floor::floor_architecture::RoomCoordinates;
+
+    use super::*;
+
+    #[test]
+    fn selects_only_matching_rules() {
+        let sut = RuleSelection {
+            rules: vec![
+                BuilderRule::new(0, 0),
+                BuilderRule {
+                    floors: Some(-10..0),
+                    ..BuilderRule::new(1, 10)
+                },
+                BuilderRule {
+                    condition: Some(Box::new(|r| r.stair_up)),
+                    ..BuilderRule::new(2, 10)
+                },
+                BuilderRule::new(3, 1),
+            ],
+            ..Default::default()
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+        let room = FloorRoom::default();
+
+        for _ in 0..20 {
+            let result = sut.select(&mut rng, &create_floor_layout(0), &room, &[]);
+            assert_eq!(Some(3), result);
+        }
+    }
+
+    #[test]
+    fn selects_rules_for_floor_and_room() {
+        let sut = RuleSelection {
+            rules: vec![
+                BuilderRule {
+                    floors: Some(-10..0),
+                    ..Default::default()
+                },
+                BuilderRule {
+                    condition: Some(Box::new(|r| r.stair_up)),
+                    ..BuilderRule::new(1, 1)
+                },
+            ],
+            ..Default::default()
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+        let stair_room = FloorRoom {
+            stair_up: true,
+            ..Default::default()
+        };
+
+        let deep_floor = sut.select(
+            &mut rng,
+            &create_floor_layout(-3),
+            &FloorRoom::default(),
+            &[],
+        );
+        let stair_room = sut.select(&mut rng, &create_floor_layout(0), &stair_room, &[]);
+        let no_match = sut.select(
+            &mut rng,
+            &create_floor_layout(0),
+            &FloorRoom::default(),
+            &[],
+        );
+
+        assert_eq!(Some(0), deep_floor);
+        assert_eq!(Some(1), stair_room);
+        assert_eq!(None, no_match);
+    }
+
+    #[test]
+    fn avoids_same_builder_for_neighbors() {
+        let sut = RuleSelection {
+            rules: vec![BuilderRule::new(0, 100), BuilderRule::new(1, 1)],
+            distinct_neighbors: true,
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+        let room = FloorRoom {
+            coords: RoomCoordinates { col: 1, row: 0 },
+            ..Default::default()
+        };
+        let created = [(DungeonCoordinates::new(0, 0, 0), 0)];
+
+        let result = sut.select(&mut rng, &create_floor_layout(0), &room, &created);
+
+        assert_eq!(Some(1), result);
+    }
+
+    #[test]
+    fn uses_same_builder_for_neighbors_without_alternative() {
+        let sut = RuleSelection {
+            rules: vec![BuilderRule::new(0, 1)],
+            distinct_neighbors: true,
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+        let room = FloorRoom {
+            coords: RoomCoordinates { col: 1, row: 0 },
+            ..Default::default()
+        };
+        let created = [(DungeonCoordinates::new(0, 0, 0), 0)];
+
+        let result = sut.select(&mut rng, &create_floor_layout(0), &room, &created);
+
+        assert_eq!(Some(0), result);
+    }
+
+    fn create_floor_layout(floor: i32) -> FloorLayout {
+        FloorLayout {
+            rooms: vec![],
+            floor,
+            dungeon_seed: 0,
+        }
+    }
+}
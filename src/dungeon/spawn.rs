@@ -0,0 +1,402 @@
+use std::ops::Range;
+
+use rand::{seq::SliceRandom, Rng};
+use rand_pcg::Pcg64;
+
+use crate::{
+    error::GenerationError,
+    room::{distance::DistanceMap, pathfinding::walking_cost, tile::DungeonTile},
+};
+
+use super::{
+    room::ArrangedDungeonRoom,
+    seed::{seeded_rng, spawn_seed},
+};
+
+/// An entity or item which can be spawned in a room
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SpawnEntry {
+    /// Id of the entity, as known to the game
+    pub entity: String,
+    /// Relative weight among all entries of the spawn table
+    pub weight: u32,
+    /// Number of entities spawned together whenever the entry is chosen
+    pub count: Range<usize>,
+    /// Minimum walking distance from the entry of the room, or from its exits if it has no entry.
+    /// Rooms with neither measure it from the first tile of their pathing, like stairs are placed.
+    pub min_entry_distance: u32,
+    /// Whether the entity has to be placed next to a wall
+    pub against_wall: bool,
+    /// Difficulties of the rooms the entry can be chosen for; None for rooms of any difficulty
+    pub difficulty: Option<Range<u32>>,
+}
+
+impl Default for SpawnEntry {
+    fn default() -> Self {
+        Self {
+            entity: String::new(),
+            weight: 1,
+            count: 1..2,
+            min_entry_distance: 0,
+            against_wall: false,
+            difficulty: None,
+        }
+    }
+}
+
+/// Entries to choose spawns from, randomly by their weights
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SpawnTable {
+    pub entries: Vec<SpawnEntry>,
+    /// Number of times an entry is chosen for a room
+    pub rolls: Range<usize>,
+    /// Minimum distance in tiles between any two spawns
+    pub spacing: u32,
+}
+
+impl Default for SpawnTable {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            rolls: 1..4,
+            spacing: 3,
+        }
+    }
+}
+
+/// An entity placed on a tile of a room
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spawn {
+    pub entity: String,
+    pub tile: usize,
+}
+
+/// Places the spawns of the table on the floor tiles of the room's pathing.
+/// Exits, stairs and all other special tiles are never used, and spawns keep the spacing of the table
+/// from each other; spawns which find no fitting tile are left out.
+///
+/// Only entries whose difficulty range contains the difficulty of the room are chosen.
+/// The spawns are drawn with a seed derived from the dungeon seed and the coordinates of the room,
+/// so the same room always gets the same spawns.
+pub fn place_spawns(
+    dungeon_seed: u64,
+    room: &ArrangedDungeonRoom,
+    table: &SpawnTable,
+) -> Result<Vec<Spawn>, GenerationError> {
+    validate(table)?;
+    let rng = &mut seeded_rng::<Pcg64>(spawn_seed(dungeon_seed, room.dungeon_coords));
+
+    let mut sources: Vec<usize> = match room.entry {
+        Some((idx, _)) => vec![idx],
+        None => room
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| **t == DungeonTile::Exit)
+            .map(|(idx, _)| idx)
+            .chain(room.exits.iter().map(|(idx, _)| *idx))
+            .collect(),
+    };
+    if sources.is_empty() {
+        sources.extend(room.pathing.first());
+    }
+    let distances = DistanceMap::new(room, &sources, walking_cost);
+
+    let mut candidates: Vec<usize> = room
+        .pathing
+        .iter()
+        .copied()
+        .filter(|idx| room.tiles[*idx] == DungeonTile::Floor)
+        .collect();
+    candidates.shuffle(rng);
+
+    let entries: Vec<&SpawnEntry> = table
+        .entries
+        .iter()
+        .filter(|e| {
+            e.difficulty
+                .as_ref()
+                .is_none_or(|d| d.contains(&room.difficulty))
+        })
+        .collect();
+    let mut result: Vec<Spawn> = vec![];
+    if entries.iter().all(|e| e.weight == 0) {
+        return Ok(result);
+    }
+
+    for _ in 0..rng.gen_range(table.rolls.clone()) {
+        let entry = entries
+            .choose_weighted(rng, |e| e.weight)
+            .map_err(|e| GenerationError::InvalidConfig(e.to_string()))?;
+
+        for _ in 0..rng.gen_range(entry.count.clone()) {
+            let tile = candidates.iter().copied().find(|idx| {
+                distances.distance(*idx).unwrap_or(0) >= entry.min_entry_distance
+                    && (!entry.against_wall || next_to_wall(room, *idx))
+                    && result
+                        .iter()
+                        .all(|s| spaced(room, s.tile, *idx, table.spacing))
+            });
+
+            if let Some(tile) = tile {
+                candidates.retain(|idx| *idx != tile);
+                result.push(Spawn {
+                    entity: entry.entity.clone(),
+                    tile,
+                });
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn validate(table: &SpawnTable) -> Result<(), GenerationError> {
+    if table.rolls.is_empty() {
+        return Err(GenerationError::InvalidConfig(
+            "rolls of the spawn table must not be empty".to_string(),
+        ));
+    }
+
+    match table.entries.iter().find(|e| e.count.is_empty()) {
+        Some(entry) => Err(GenerationError::InvalidConfig(format!(
+            "count of spawn entry {} must not be empty",
+            entry.entity
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn next_to_wall(room: &ArrangedDungeonRoom, idx: usize) -> bool {
+    let (row, col) = (room.row(idx), room.col(idx));
+    [
+        (row > 0).then(|| idx - room.columns),
+        (col > 0).then(|| idx - 1),
+        (col < room.columns - 1).then(|| idx + 1),
+        (row < room.rows - 1).then(|| idx + room.columns),
+    ]
+    .iter()
+    .flatten()
+    .any(|n| room.tiles[*n] == DungeonTile::Wall)
+}
+
+// whether two tiles are at least the spacing apart, measured as the crow flies
+fn spaced(room: &ArrangedDungeonRoom, a: usize, b: usize, spacing: u32) -> bool {
+    let rows = room.row(a).abs_diff(room.row(b)) as u64;
+    let cols = room.col(a).abs_diff(room.col(b)) as u64;
+    let spacing = spacing as u64;
+
+    rows * rows + cols * cols >= spacing * spacing
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        direction::Direction3D, dungeon::coords::DungeonCoordinates, room::print::parse_room,
+    };
+
+    use super::*;
+
+    #[test]
+    fn places_spawns_on_floor_tiles_with_spacing() {
+        let room = create_room();
+        let table = SpawnTable {
+            entries: vec![SpawnEntry {
+                entity: "rat".to_string(),
+                count: 2..5,
+                ..Default::default()
+            }],
+            rolls: 3..4,
+            spacing: 2,
+        };
+
+        let result = place_spawns(1, &room, &table).unwrap();
+
+        assert!(result.len() >= 6);
+        for (idx, spawn) in result.iter().enumerate() {
+            assert_eq!("rat", spawn.entity);
+            assert_eq!(DungeonTile::Floor, room.tiles[spawn.tile]);
+            assert!(room.pathing.contains(&spawn.tile));
+            for other in result[idx + 1..].iter() {
+                assert!(spaced(&room, spawn.tile, other.tile, 2));
+            }
+        }
+    }
+
+    #[test]
+    fn places_same_spawns_for_same_seed() {
+        let room = create_room();
+        let table = create_table();
+
+        let first = place_spawns(7, &room, &table).unwrap();
+        let second = place_spawns(7, &room, &table).unwrap();
+
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn keeps_distance_to_entry_and_walls() {
+        let mut room = create_room();
+        room.entry = Some((room.room_idx(3, 0), Direction3D::Left));
+        let table = SpawnTable {
+            entries: vec![SpawnEntry {
+                entity: "chest".to_string(),
+                count: 3..4,
+                min_entry_distance: 5,
+                against_wall: true,
+                ..Default::default()
+            }],
+            rolls: 1..2,
+            spacing: 1,
+        };
+
+        let result = place_spawns(3, &room, &table).unwrap();
+
+        assert_eq!(3, result.len());
+        for spawn in result {
+            // the room is open, so the walking distance equals the manhattan distance
+            assert!(room.row(spawn.tile).abs_diff(3) + room.col(spawn.tile) >= 5);
+            assert!(next_to_wall(&room, spawn.tile));
+        }
+    }
+
+    #[test]
+    fn chooses_entries_by_room_difficulty() {
+        let mut room = create_room();
+        let table = SpawnTable {
+            entries: vec![
+                SpawnEntry {
+                    entity: "rat".to_string(),
+                    difficulty: Some(0..5),
+                    ..Default::default()
+                },
+                SpawnEntry {
+                    entity: "troll".to_string(),
+                    difficulty: Some(5..10),
+                    ..Default::default()
+                },
+            ],
+            rolls: 3..4,
+            spacing: 1,
+        };
+
+        let easy = place_spawns(1, &room, &table).unwrap();
+        room.difficulty = 7;
+        let hard = place_spawns(1, &room, &table).unwrap();
+        room.difficulty = 12;
+        let none = place_spawns(1, &room, &table).unwrap();
+
+        assert!(!easy.is_empty() && easy.iter().all(|s| s.entity == "rat"));
+        assert!(!hard.is_empty() && hard.iter().all(|s| s.entity == "troll"));
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn keeps_distance_in_rooms_without_entry_and_exits() {
+        let mut room = parse_room(
+            "#######\n\
+             #.....#\n\
+             #######",
+        )
+        .unwrap();
+        room.pathing();
+        let room = ArrangedDungeonRoom::from(&room);
+        let table = SpawnTable {
+            entries: vec![SpawnEntry {
+                entity: "bat".to_string(),
+                count: 5..6,
+                min_entry_distance: 3,
+                ..Default::default()
+            }],
+            rolls: 1..2,
+            spacing: 1,
+        };
+
+        let result = place_spawns(1, &room, &table).unwrap();
+
+        let first = room.pathing[0];
+        assert_eq!(2, result.len());
+        for spawn in result {
+            assert!(room.col(spawn.tile).abs_diff(room.col(first)) >= 3);
+        }
+    }
+
+    #[test]
+    fn allows_large_spacing() {
+        let room = create_room();
+
+        assert!(!spaced(&room, 0, 1, u32::MAX));
+        assert!(spaced(&room, 0, 1, 1));
+    }
+
+    #[test]
+    fn rejects_empty_ranges() {
+        let room = create_room();
+        let table = SpawnTable {
+            entries: vec![SpawnEntry {
+                count: 2..2,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let result = place_spawns(1, &room, &table);
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn places_nothing_without_entries() {
+        let result = place_spawns(1, &create_room(), &SpawnTable::default());
+
+        assert_eq!(Ok(vec![]), result);
+    }
+
+    fn create_table() -> SpawnTable {
+        SpawnTable {
+            entries: vec![
+                SpawnEntry {
+                    entity: "goblin".to_string(),
+                    weight: 3,
+                    count: 1..3,
+                    ..Default::default()
+                },
+                SpawnEntry {
+                    entity: "potion".to_string(),
+                    against_wall: true,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn create_room() -> ArrangedDungeonRoom {
+        let mut room = parse_room(
+            "##########\n\
+             #........#\n\
+             #...^....#\n\
+             E........E\n\
+             #........#\n\
+             #........#\n\
+             ##########",
+        )
+        .unwrap();
+        room.pathing();
+        let mut arranged = ArrangedDungeonRoom::from(&room);
+        arranged.dungeon_coords = DungeonCoordinates::new(0, 1, 2);
+        arranged
+    }
+}
@@ -0,0 +1,309 @@
+use std::cmp::{max, min};
+
+use crate::{
+    direction::Direction3D,
+    room::{
+        attributes::TileAttributes,
+        math::UPosition,
+        tile::{DungeonTile, TileGrid},
+    },
+};
+
+use super::{coords::DungeonCoordinates, print::fill_floor_grid, room::ArrangedDungeonRoom};
+
+// tiles between two grid cells, where corridors connect the exits of neighboring rooms
+const CELL_GAP: usize = 1;
+
+/// All rooms of one floor stitched together into a single tile map.
+/// Rooms are arranged like in print_floor, and matching exits of neighboring rooms are connected with corridors.
+/// Tiles which belong to neither a room nor a corridor are walls.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct FloorMap {
+    pub floor: i32,
+    pub rows: usize,
+    pub columns: usize,
+    pub tiles: Vec<DungeonTile>,
+    /// Position of the upper left tile of each room within the floor map
+    pub origins: Vec<(DungeonCoordinates, UPosition)>,
+    /// Attributes of the tiles of all rooms, at their positions within the floor map
+    pub attributes: TileAttributes,
+}
+
+impl TileGrid for FloorMap {
+    fn grid_tiles(&self) -> &[DungeonTile] {
+        &self.tiles
+    }
+
+    fn grid_rows(&self) -> usize {
+        self.rows
+    }
+
+    fn grid_columns(&self) -> usize {
+        self.columns
+    }
+}
+
+impl FloorMap {
+    pub fn map_idx(&self, row: usize, col: usize) -> usize {
+        (row * self.columns) + col
+    }
+
+    /// Translates the index of a tile within the room at the given coordinates into an index of the floor map
+    pub fn from_room_idx(&self, room: &ArrangedDungeonRoom, idx: usize) -> Option<usize> {
+        let (_, origin) = self
+            .origins
+            .iter()
+            .find(|(coords, _)| *coords == room.dungeon_coords)?;
+
+        Some(self.map_idx(origin.row + room.row(idx), origin.col + room.col(idx)))
+    }
+
+    fn dig(&mut self, from: UPosition, to: UPosition) {
+        for row in min(from.row, to.row)..=max(from.row, to.row) {
+            for col in min(from.col, to.col)..=max(from.col, to.col) {
+                let idx = self.map_idx(row, col);
+                if self.tiles[idx] == DungeonTile::Wall {
+                    self.tiles[idx] = DungeonTile::Floor;
+                }
+            }
+        }
+    }
+}
+
+/// Stitches the rooms of a single floor into one floor map
+pub fn stitch_floor(rooms: Vec<&ArrangedDungeonRoom>) -> FloorMap {
+    if rooms.is_empty() {
+        return FloorMap::default();
+    }
+
+    let grid = fill_floor_grid(rooms.clone());
+    let row_starts = cell_starts(&grid.max_heights);
+    let col_starts = cell_starts(&grid.max_widths);
+    let rows = row_starts.last().unwrap() + grid.max_heights.last().unwrap();
+    let columns = col_starts.last().unwrap() + grid.max_widths.last().unwrap();
+
+    let mut map = FloorMap {
+        floor: rooms[0].dungeon_coords.floor,
+        rows,
+        columns,
+        tiles: vec![DungeonTile::Wall; rows * columns],
+        origins: vec![],
+        attributes: TileAttributes::default(),
+    };
+
+    for room in rooms.iter() {
+        let grid_row = (room.dungeon_coords.row + grid.row_offset) as usize;
+        let grid_col = (room.dungeon_coords.col + grid.col_offset) as usize;
+        let origin = UPosition::new(
+            row_starts[grid_row] + grid.top_pads[grid_row][grid_col],
+            col_starts[grid_col] + grid.left_pads[grid_row][grid_col],
+        );
+
+        for (idx, tile) in room.tiles.iter().enumerate() {
+            let map_idx = map.map_idx(origin.row + room.row(idx), origin.col + room.col(idx));
+            map.tiles[map_idx] = *tile;
+        }
+        let columns = map.columns;
+        map.attributes.merge(&room.attributes, |idx| {
+            (origin.row + room.row(idx)) * columns + origin.col + room.col(idx)
+        });
+
+        map.origins.push((room.dungeon_coords, origin));
+    }
+
+    for room in rooms.iter() {
+        let coords = room.dungeon_coords;
+        let grid_row = (coords.row + grid.row_offset) as usize;
+        let grid_col = (coords.col + grid.col_offset) as usize;
+
+        let right = DungeonCoordinates::new(coords.floor, coords.col + 1, coords.row);
+        if let Some(neighbor) = rooms.iter().find(|r| r.dungeon_coords == right) {
+            let gap_col = col_starts[grid_col] + grid.max_widths[grid_col];
+            connect(&mut map, room, neighbor, Direction3D::Right, |from, to| {
+                vec![
+                    UPosition::new(from.row, gap_col),
+                    UPosition::new(to.row, gap_col),
+                ]
+            });
+        }
+
+        let bottom = DungeonCoordinates::new(coords.floor, coords.col, coords.row + 1);
+        if let Some(neighbor) = rooms.iter().find(|r| r.dungeon_coords == bottom) {
+            let gap_row = row_starts[grid_row] + grid.max_heights[grid_row];
+            connect(&mut map, room, neighbor, Direction3D::Bottom, |from, to| {
+                vec![
+                    UPosition::new(gap_row, from.col),
+                    UPosition::new(gap_row, to.col),
+                ]
+            });
+        }
+    }
+
+    map
+}
+
+// Start of every grid row or column within the floor map, given the maximum sizes of the rooms in them
+fn cell_starts(max_sizes: &[usize]) -> Vec<usize> {
+    let mut starts = vec![];
+    let mut next = 0;
+    for size in max_sizes {
+        starts.push(next);
+        next += size + CELL_GAP;
+    }
+
+    starts
+}
+
+// Digs a corridor from the exit of a room in the given direction to the opposite exit of its neighbor.
+// The corridor runs through the turning points calculated from the positions of both exits.
+fn connect<T>(
+    map: &mut FloorMap,
+    room: &ArrangedDungeonRoom,
+    neighbor: &ArrangedDungeonRoom,
+    direction: Direction3D,
+    turning_points: T,
+) where
+    T: Fn(UPosition, UPosition) -> Vec<UPosition>,
+{
+    let from = match exit_position(map, room, direction) {
+        Some(p) => p,
+        None => return,
+    };
+    let to = match exit_position(map, neighbor, direction.opposite()) {
+        Some(p) => p,
+        None => return,
+    };
+
+    let mut points = vec![from];
+    points.append(&mut turning_points(from, to));
+    points.push(to);
+    for pair in points.windows(2) {
+        map.dig(pair[0], pair[1]);
+    }
+}
+
+// Position of the central exit tile on one side of a room within the floor map
+fn exit_position(
+    map: &FloorMap,
+    room: &ArrangedDungeonRoom,
+    direction: Direction3D,
+) -> Option<UPosition> {
+    let exits = room.exit_tiles(direction);
+    let exit = exits.get(exits.len() / 2)?;
+    let map_idx = map.from_room_idx(room, *exit)?;
+
+    Some(UPosition::new(map_idx / map.columns, map_idx % map.columns))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::room::{
+        distance::DistanceMap, pathfinding::walking_cost, print::parse_room, print::print_room,
+    };
+
+    use super::*;
+
+    #[test]
+    fn stitches_empty_floor() {
+        let result = stitch_floor(vec![]);
+
+        assert_eq!(0, result.tiles.len());
+    }
+
+    #[test]
+    fn stitches_neighboring_rooms_with_corridors() {
+        let left = create_room(0, 0, "#####\n#...#\n#...E\n#...#\n##E##");
+        let right = create_room(0, 1, "###\nE.#\n###");
+        let below = create_room(1, 0, "#E#\n#.#\n###");
+        let rooms = vec![&left, &right, &below];
+
+        let result = stitch_floor(rooms);
+
+        let expected = "#########
+#...#####
+#...E.E.#
+#...#####
+##E######
+##.######
+##E######
+##.######
+#########";
+        assert_eq!(
+            expected,
+            print_room(result.rows, result.columns, result.tiles, 0, 0)
+        );
+    }
+
+    #[test]
+    fn connects_exits_of_all_rooms() {
+        let left = create_room(0, 0, "#####\n#...#\n#...E\n#...#\n##E##");
+        let right = create_room(0, 1, "###\nE.#\n###");
+        let below = create_room(1, 0, "#E#\n#.#\n###");
+        let rooms = vec![&left, &right, &below];
+
+        let result = stitch_floor(rooms);
+
+        let start = result.from_room_idx(&left, 6).unwrap();
+        let distances = DistanceMap::new(&result, &[start], walking_cost);
+        for room in [&left, &right, &below] {
+            for idx in room.pathing.iter() {
+                let map_idx = result.from_room_idx(room, *idx).unwrap();
+                assert!(distances.distance(map_idx).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn moves_attributes_into_floor_map() {
+        let mut left = create_room(0, 0, "#####\n#...#\n#...E\n#...#\n##E##");
+        let mut right = create_room(0, 1, "###\nE.#\n###");
+        left.attributes.set("biome", 6, "cave");
+        right.attributes.set("biome", 4, "swamp");
+
+        let result = stitch_floor(vec![&left, &right]);
+
+        let left_idx = result.from_room_idx(&left, 6).unwrap();
+        let right_idx = result.from_room_idx(&right, 4).unwrap();
+        assert_eq!(
+            Some("cave"),
+            result
+                .attributes
+                .get("biome", left_idx)
+                .and_then(|v| v.as_text())
+        );
+        assert_eq!(
+            Some("swamp"),
+            result
+                .attributes
+                .get("biome", right_idx)
+                .and_then(|v| v.as_text())
+        );
+    }
+
+    #[test]
+    fn places_rooms_with_negative_coordinates() {
+        let first = create_room(-1, -1, "...\n...");
+        let second = create_room(0, 0, ".\n.");
+
+        let result = stitch_floor(vec![&first, &second]);
+
+        assert_eq!(5, result.rows);
+        assert_eq!(5, result.columns);
+        assert_eq!(
+            vec![
+                (DungeonCoordinates::new(0, -1, -1), UPosition::new(0, 0)),
+                (DungeonCoordinates::new(0, 0, 0), UPosition::new(3, 4)),
+            ],
+            result.origins
+        );
+    }
+
+    fn create_room(row: i32, col: i32, tiles: &str) -> ArrangedDungeonRoom {
+        let mut room = parse_room(tiles).unwrap();
+        room.pathing();
+        let mut arranged = ArrangedDungeonRoom::from(&room);
+        arranged.list_border_exits();
+        arranged.dungeon_coords = DungeonCoordinates::new(0, col, row);
+        arranged
+    }
+}
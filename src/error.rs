@@ -0,0 +1,43 @@
+use std::fmt;
+
+use crate::dungeon::coords::DungeonCoordinates;
+
+/// Reasons why generating a room, floor or dungeon failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerationError {
+    /// A parameter of a builder or layout config is out of its valid range
+    InvalidConfig(String),
+    /// The generator did not find a valid result within its attempt budget
+    AttemptsExhausted {
+        generator: &'static str,
+        attempts: usize,
+    },
+    /// A generated room has no tile that can be walked on
+    NoWalkableTiles,
+    /// There is no room at the given dungeon coordinates
+    RoomNotFound(DungeonCoordinates),
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidConfig(reason) => write!(f, "invalid config: {}", reason),
+            Self::AttemptsExhausted {
+                generator,
+                attempts,
+            } => write!(
+                f,
+                "{} found no valid result within {} attempts",
+                generator, attempts
+            ),
+            Self::NoWalkableTiles => write!(f, "room has no walkable tiles"),
+            Self::RoomNotFound(coords) => write!(
+                f,
+                "no room at floor {}, column {}, row {}",
+                coords.floor, coords.col, coords.row
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {}
@@ -1,24 +1,35 @@
-use std::cmp::Ordering;
+use std::cmp::{max, min, Ordering};
 
 use rand::prelude::*;
-use rand_pcg::Pcg64;
 
 use crate::room::math::Position;
 
-use crate::direction::Direction3D;
+use crate::{direction::Direction3D, dungeon::role::RoomRole, error::GenerationError};
+
+// steps of the random walk in randomized_layout per room of the floor, before giving up
+const LAYOUT_STEPS_PER_ROOM: usize = 1000;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FloorLayout {
     pub rooms: Vec<FloorRoom>,
     pub floor: i32,
+    /// Seed of the dungeon the floor belongs to, from which the seeds of its rooms are derived
+    pub dungeon_seed: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FloorRoom {
     pub coords: RoomCoordinates,
     pub exits: Vec<Direction3D>,
     pub stair_up: bool,
     pub stair_down: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub role: RoomRole,
+    /// Difficulty score of the room, see DifficultyConfig
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub difficulty: u32,
 }
 
 impl Default for FloorRoom {
@@ -28,11 +39,27 @@ impl Default for FloorRoom {
             exits: vec![],
             stair_down: false,
             stair_up: false,
+            role: RoomRole::Normal,
+            difficulty: 0,
         }
     }
 }
 
+/// How the adjacent rooms of a floor are connected with exits
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FloorConnections {
+    /// Every pair of adjacent rooms is connected
+    #[default]
+    All,
+    /// A random spanning tree, with exactly one path between any two rooms, plus the given number of extra connections forming loops
+    Loops(usize),
+    /// A random spanning tree plus the given percentage of the remaining pairs of adjacent rooms, forming loops
+    LoopPercent(u8),
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoomCoordinates {
     pub col: i32,
     pub row: i32,
@@ -87,11 +114,25 @@ impl RoomCoordinates {
 pub fn create_floor_layout(
     floor_size: u8,
     floor_number: i32,
-    rng: &mut Pcg64,
+    rng: &mut dyn RngCore,
     start_room: Position,
+    connections: FloorConnections,
 ) -> FloorLayout {
-    let coords = randomized_layout(floor_size, rng, start_room);
-    let room_config = get_exits(&coords);
+    try_create_floor_layout(floor_size, floor_number, rng, start_room, connections)
+        .expect("unable to create floor layout")
+}
+
+/// Fallible variant of create_floor_layout, which fails if the random walk does not reach the floor size within its step budget
+pub fn try_create_floor_layout(
+    floor_size: u8,
+    floor_number: i32,
+    rng: &mut dyn RngCore,
+    start_room: Position,
+    connections: FloorConnections,
+) -> Result<FloorLayout, GenerationError> {
+    let coords = randomized_layout(floor_size, rng, start_room)?;
+    let connected = choose_connections(&coords, connections, rng);
+    let room_config = get_exits(&coords, &connected);
 
     let rooms: Vec<FloorRoom> = room_config
         .into_iter()
@@ -100,25 +141,38 @@ pub fn create_floor_layout(
             exits: conf.1,
             stair_up: false,
             stair_down: false,
+            role: RoomRole::Normal,
+            difficulty: 0,
         })
         .collect();
 
-    FloorLayout {
+    Ok(FloorLayout {
         rooms,
         floor: floor_number,
-    }
+        dungeon_seed: 0,
+    })
 }
 
 /// Will "dig" randomly from a start point, until floor size is reached
 fn randomized_layout(
     floor_size: u8,
-    rng: &mut Pcg64,
+    rng: &mut dyn RngCore,
     start_room: Position,
-) -> Vec<RoomCoordinates> {
+) -> Result<Vec<RoomCoordinates>, GenerationError> {
     let mut pos = RoomCoordinates::from_position(start_room);
     let mut layout = vec![pos.clone()];
+    let max_steps = floor_size as usize * LAYOUT_STEPS_PER_ROOM;
+    let mut steps = 0;
 
     while layout.len() < (floor_size as usize) {
+        if steps == max_steps {
+            return Err(GenerationError::AttemptsExhausted {
+                generator: "floor layout",
+                attempts: steps,
+            });
+        }
+
+        steps += 1;
         match rng.gen_range(0..4) {
             0 => pos.col += 1,
             1 => pos.col -= 1,
@@ -132,27 +186,100 @@ fn randomized_layout(
         }
     }
 
-    layout
+    Ok(layout)
+}
+
+/// Pairs of adjacent rooms, as indices into the room coordinates, with the lower index first
+fn adjacent_pairs(all_coords: &[RoomCoordinates]) -> Vec<(usize, usize)> {
+    let mut result = vec![];
+    for (idx, coords) in all_coords.iter().enumerate() {
+        for neighbor in [coords.clone_delta_col(1), coords.clone_delta_row(1)] {
+            if let Some(neighbor_idx) = all_coords.iter().position(|c| *c == neighbor) {
+                result.push((min(idx, neighbor_idx), max(idx, neighbor_idx)));
+            }
+        }
+    }
+
+    result
+}
+
+/// Chooses the pairs of adjacent rooms connected by exits.
+/// Loops are formed by adding connections to a random spanning tree, which keeps all rooms reachable.
+fn choose_connections(
+    all_coords: &[RoomCoordinates],
+    connections: FloorConnections,
+    rng: &mut dyn RngCore,
+) -> Vec<(usize, usize)> {
+    let mut pairs = adjacent_pairs(all_coords);
+    let extra = match connections {
+        FloorConnections::All => return pairs,
+        FloorConnections::Loops(extra) => extra,
+        FloorConnections::LoopPercent(percent) => {
+            // a spanning tree uses one pair less than there are rooms
+            let remaining = (pairs.len() + 1).saturating_sub(all_coords.len());
+            remaining * percent.min(100) as usize / 100
+        }
+    };
+
+    // Kruskal's algorithm on shuffled pairs creates a random spanning tree
+    pairs.shuffle(rng);
+    let mut sets: Vec<usize> = (0..all_coords.len()).collect();
+    let mut tree = vec![];
+    let mut loops = vec![];
+    for (first, second) in pairs {
+        let first_set = find_set(&mut sets, first);
+        let second_set = find_set(&mut sets, second);
+        if first_set == second_set {
+            loops.push((first, second));
+        } else {
+            sets[first_set] = second_set;
+            tree.push((first, second));
+        }
+    }
+
+    tree.extend(loops.into_iter().take(extra));
+    tree
+}
+
+// Representative of the set containing the room, for the union-find structure of the spanning tree
+fn find_set(sets: &mut [usize], idx: usize) -> usize {
+    let mut root = idx;
+    while sets[root] != root {
+        root = sets[root];
+    }
+    sets[idx] = root;
+
+    root
 }
 
-/// Checks rooms for adjoined rooms and sets exit directions accordingly
-fn get_exits(all_coords: &Vec<RoomCoordinates>) -> Vec<(&RoomCoordinates, Vec<Direction3D>)> {
+/// Sets the exit directions of every room towards the rooms it is connected with
+fn get_exits<'a>(
+    all_coords: &'a [RoomCoordinates],
+    connected: &[(usize, usize)],
+) -> Vec<(&'a RoomCoordinates, Vec<Direction3D>)> {
+    let is_connected = |coords: &RoomCoordinates, neighbor: RoomCoordinates| {
+        connected.iter().any(|(first, second)| {
+            let pair = (all_coords[*first], all_coords[*second]);
+            pair == (*coords, neighbor) || pair == (neighbor, *coords)
+        })
+    };
+
     let mut result = vec![];
     for coords in all_coords {
         let mut exits = vec![];
-        if all_coords.contains(&coords.clone_delta_col(1)) {
+        if is_connected(coords, coords.clone_delta_col(1)) {
             exits.push(Direction3D::Right);
         }
 
-        if all_coords.contains(&coords.clone_delta_col(-1)) {
+        if is_connected(coords, coords.clone_delta_col(-1)) {
             exits.push(Direction3D::Left);
         }
 
-        if all_coords.contains(&coords.clone_delta_row(-1)) {
+        if is_connected(coords, coords.clone_delta_row(-1)) {
             exits.push(Direction3D::Top);
         }
 
-        if all_coords.contains(&coords.clone_delta_row(1)) {
+        if is_connected(coords, coords.clone_delta_row(1)) {
             exits.push(Direction3D::Bottom);
         }
 
@@ -175,7 +302,8 @@ mod test {
         let mut rng = Pcg64::seed_from_u64(1);
 
         // act
-        let result = create_floor_layout(8, 0, &mut rng, Position::new(0, 0));
+        let result =
+            create_floor_layout(8, 0, &mut rng, Position::new(0, 0), FloorConnections::All);
 
         // assert
         assert_eq!(8, result.rooms.len());
@@ -211,11 +339,79 @@ mod test {
             RoomCoordinates { col: 0, row: 1 },
             RoomCoordinates { col: 0, row: 0 },
         ];
-        let results = get_exits(&room_coords);
+        let results = get_exits(&room_coords, &adjacent_pairs(&room_coords));
 
         assert_eq!(3, results.len());
         assert_eq!(vec![Direction3D::Left], results[0].1);
         assert_eq!(vec![Direction3D::Right, Direction3D::Top], results[1].1);
         assert_eq!(vec![Direction3D::Bottom], results[2].1);
     }
+
+    #[test]
+    fn connects_rooms_as_tree_without_loops() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let coords = create_square_coords();
+
+        let result = choose_connections(&coords, FloorConnections::Loops(0), &mut rng);
+
+        assert_eq!(coords.len() - 1, result.len());
+        assert_all_connected(&coords, &result);
+    }
+
+    #[test]
+    fn adds_loops_to_tree() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let coords = create_square_coords();
+
+        let two_loops = choose_connections(&coords, FloorConnections::Loops(2), &mut rng);
+        let all_loops = choose_connections(&coords, FloorConnections::LoopPercent(100), &mut rng);
+        let half_loops = choose_connections(&coords, FloorConnections::LoopPercent(50), &mut rng);
+
+        // 9 rooms in a 3x3 square have 12 adjacent pairs, 8 of them form a spanning tree
+        assert_eq!(10, two_loops.len());
+        assert_eq!(12, all_loops.len());
+        assert_eq!(10, half_loops.len());
+        assert_all_connected(&coords, &two_loops);
+    }
+
+    #[test]
+    fn sets_exits_only_for_chosen_connections() {
+        let mut rng = Pcg64::seed_from_u64(3);
+
+        let result = create_floor_layout(
+            8,
+            0,
+            &mut rng,
+            Position::new(0, 0),
+            FloorConnections::Loops(0),
+        );
+
+        let exit_count: usize = result.rooms.iter().map(|r| r.exits.len()).sum();
+        assert_eq!(2 * 7, exit_count);
+    }
+
+    fn create_square_coords() -> Vec<RoomCoordinates> {
+        (0..9)
+            .map(|idx| RoomCoordinates {
+                col: idx % 3,
+                row: idx / 3,
+            })
+            .collect()
+    }
+
+    fn assert_all_connected(coords: &[RoomCoordinates], connected: &[(usize, usize)]) {
+        let mut reached = vec![0];
+        while let Some((first, second)) = connected
+            .iter()
+            .find(|(first, second)| reached.contains(first) != reached.contains(second))
+        {
+            reached.push(if reached.contains(first) {
+                *second
+            } else {
+                *first
+            });
+        }
+
+        assert_eq!(coords.len(), reached.len());
+    }
 }
@@ -45,6 +45,7 @@ mod test {
     fn create_floor_layout() -> FloorLayout {
         FloorLayout {
             floor: 1,
+            dungeon_seed: 0,
             rooms: vec![
                 FloorRoom {
                     coords: RoomCoordinates { col: 1, row: 0 },
@@ -1,5 +1,6 @@
 pub mod direction;
 pub mod dungeon;
+pub mod error;
 pub mod floor;
 pub mod room;
 
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+/// Value of a tile attribute
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeValue {
+    /// A marker, e.g. for spawn points
+    Flag(bool),
+    /// A number, e.g. for elevation, light levels or decoration ids
+    Number(i64),
+    /// A name, e.g. for biomes
+    Text(String),
+}
+
+impl AttributeValue {
+    pub fn as_flag(&self) -> Option<bool> {
+        match self {
+            AttributeValue::Flag(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<i64> {
+        match self {
+            AttributeValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            AttributeValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(value: bool) -> Self {
+        AttributeValue::Flag(value)
+    }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(value: i64) -> Self {
+        AttributeValue::Number(value)
+    }
+}
+
+impl From<i32> for AttributeValue {
+    fn from(value: i32) -> Self {
+        AttributeValue::Number(value.into())
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::Text(value)
+    }
+}
+
+/// Named layers of attributes on the tiles of a room, next to the tiles themselves.
+/// Every layer only holds the tiles it has a value for, keyed by tile index.
+/// The meaning of layers and values is up to the game, e.g. a "biome" layer with texts
+/// or an "elevation" layer with numbers.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TileAttributes {
+    pub layers: BTreeMap<String, BTreeMap<usize, AttributeValue>>,
+}
+
+impl TileAttributes {
+    pub fn is_empty(&self) -> bool {
+        self.layers.values().all(|l| l.is_empty())
+    }
+
+    pub fn set<V: Into<AttributeValue>>(&mut self, layer: &str, idx: usize, value: V) {
+        self.layers
+            .entry(layer.to_string())
+            .or_default()
+            .insert(idx, value.into());
+    }
+
+    pub fn get(&self, layer: &str, idx: usize) -> Option<&AttributeValue> {
+        self.layers.get(layer).and_then(|l| l.get(&idx))
+    }
+
+    pub fn remove(&mut self, layer: &str, idx: usize) -> Option<AttributeValue> {
+        self.layers.get_mut(layer).and_then(|l| l.remove(&idx))
+    }
+
+    /// Tiles with a value in the layer, ordered by tile index
+    pub fn layer(&self, layer: &str) -> Vec<(usize, &AttributeValue)> {
+        self.layers
+            .get(layer)
+            .map(|l| l.iter().map(|(idx, value)| (*idx, value)).collect())
+            .unwrap_or_default()
+    }
+
+    /// The attributes with every tile index mapped to a new index, e.g. when a room is turned
+    pub fn map_indices<F: Fn(usize) -> usize>(&self, mapping: F) -> Self {
+        let layers = self
+            .layers
+            .iter()
+            .map(|(name, layer)| {
+                let mapped = layer
+                    .iter()
+                    .map(|(idx, value)| (mapping(*idx), value.clone()))
+                    .collect();
+                (name.clone(), mapped)
+            })
+            .collect();
+
+        Self { layers }
+    }
+
+    /// Adds all values of the other attributes, with their tile indexes mapped to indexes of these attributes
+    pub fn merge<F: Fn(usize) -> usize>(&mut self, other: &TileAttributes, mapping: F) {
+        for (name, layer) in other.layers.iter() {
+            let target = self.layers.entry(name.clone()).or_default();
+            for (idx, value) in layer.iter() {
+                target.insert(mapping(*idx), value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stores_typed_values_per_layer() {
+        let mut sut = TileAttributes::default();
+
+        sut.set("biome", 3, "swamp");
+        sut.set("elevation", 3, -2);
+        sut.set("spawn", 5, true);
+
+        assert_eq!(Some("swamp"), sut.get("biome", 3).and_then(|v| v.as_text()));
+        assert_eq!(
+            Some(-2),
+            sut.get("elevation", 3).and_then(|v| v.as_number())
+        );
+        assert_eq!(Some(true), sut.get("spawn", 5).and_then(|v| v.as_flag()));
+        assert_eq!(None, sut.get("biome", 5));
+        assert_eq!(None, sut.get("light", 3));
+    }
+
+    #[test]
+    fn removes_values() {
+        let mut sut = TileAttributes::default();
+        sut.set("spawn", 5, true);
+
+        let removed = sut.remove("spawn", 5);
+
+        assert_eq!(Some(AttributeValue::Flag(true)), removed);
+        assert!(sut.is_empty());
+    }
+
+    #[test]
+    fn maps_tile_indices() {
+        let mut sut = TileAttributes::default();
+        sut.set("light", 0, 4);
+        sut.set("light", 1, 7);
+
+        let result = sut.map_indices(|idx| idx + 10);
+
+        assert_eq!(
+            vec![
+                (10, &AttributeValue::Number(4)),
+                (11, &AttributeValue::Number(7))
+            ],
+            result.layer("light")
+        );
+    }
+
+    #[test]
+    fn merges_attributes_of_other_grid() {
+        let mut sut = TileAttributes::default();
+        sut.set("biome", 0, "cave");
+        let mut other = TileAttributes::default();
+        other.set("biome", 0, "swamp");
+        other.set("decoration", 1, 12);
+
+        sut.merge(&other, |idx| idx + 4);
+
+        assert_eq!(2, sut.layer("biome").len());
+        assert_eq!(Some(&AttributeValue::Number(12)), sut.get("decoration", 5));
+    }
+}
@@ -1,15 +1,22 @@
-use rand::Rng;
-use rand_pcg::Pcg64;
+use rand::{Rng, RngCore};
 
-use crate::{direction::Direction3D, floor::floor_architecture::FloorRoom};
+use crate::{direction::Direction3D, error::GenerationError, floor::floor_architecture::FloorRoom};
 
 use super::{room::DungeonRoom, room_builder::RoomBuilder, tile::DungeonTile};
 
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct AutomataRoomBuilder {
     pub rows: usize,
     pub cols: usize,
     pub wall_percent: u8,
     pub iterations: u8,
+    /// Number of random rooms to try, until one opens up to all required exit sides
+    pub max_attempts: u16,
 }
 
 impl Default for AutomataRoomBuilder {
@@ -19,19 +26,42 @@ impl Default for AutomataRoomBuilder {
             cols: 16,
             wall_percent: 33,
             iterations: 5,
+            max_attempts: 1000,
         }
     }
 }
 
 impl RoomBuilder for AutomataRoomBuilder {
-    fn create_room(&self, rng: &mut Pcg64, room_config: &FloorRoom) -> DungeonRoom {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create automata room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        if self.rows < 3 || self.cols < 3 {
+            return Err(GenerationError::InvalidConfig(
+                "automata rooms need at least 3 rows and columns".to_string(),
+            ));
+        }
+
         let mut exits_hit: Vec<Direction3D> = vec![];
         let mut all_exits_hit = false;
+        let mut attempts = 0;
 
         let mut room = DungeonRoom::default();
-        room.stair_down = room_config.stair_down;
-        room.stair_up = room_config.stair_up;
         while !all_exits_hit {
+            if attempts == self.max_attempts {
+                return Err(GenerationError::AttemptsExhausted {
+                    generator: "automata room builder",
+                    attempts: attempts as usize,
+                });
+            }
+
+            attempts += 1;
             room = self.random_room(rng);
             exits_hit = self.get_hit_exits(&room);
             all_exits_hit = room_config.exits.iter().all(|e| exits_hit.contains(e));
@@ -43,7 +73,10 @@ impl RoomBuilder for AutomataRoomBuilder {
             room.close_side(*non_wanted_exit_direction);
         }
 
-        room
+        room.stair_down = room_config.stair_down;
+        room.stair_up = room_config.stair_up;
+
+        Ok(room)
     }
 
     fn get_cols(&self) -> usize {
@@ -56,7 +89,7 @@ impl RoomBuilder for AutomataRoomBuilder {
 }
 
 impl AutomataRoomBuilder {
-    fn random_room(&self, rng: &mut Pcg64) -> DungeonRoom {
+    fn random_room(&self, rng: &mut dyn RngCore) -> DungeonRoom {
         let tiles = self.random_noise_map(rng);
         let mut room = DungeonRoom {
             tiles,
@@ -79,7 +112,7 @@ impl AutomataRoomBuilder {
         room
     }
 
-    fn random_noise_map(&self, rng: &mut Pcg64) -> Vec<DungeonTile> {
+    fn random_noise_map(&self, rng: &mut dyn RngCore) -> Vec<DungeonTile> {
         let mut dungeon_tiles: Vec<DungeonTile> = vec![];
         for _ in 0..(self.rows * self.cols) {
             let roll = rng.gen_range(0..100);
@@ -136,6 +169,8 @@ mod test {
         room::{print::print_room, room::DungeonRoom, tile::DungeonTile},
     };
 
+    use rand_pcg::Pcg64;
+
     use super::*;
 
     #[test]
@@ -180,6 +215,43 @@ mod test {
         );
     }
 
+    #[test]
+    fn fails_if_exits_are_not_hit_within_attempts() {
+        let sut = AutomataRoomBuilder {
+            wall_percent: 100,
+            max_attempts: 5,
+            ..Default::default()
+        };
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top],
+            ..Default::default()
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+
+        let result = sut.try_create_room(&mut rng, &room_config);
+
+        assert_eq!(
+            Err(GenerationError::AttemptsExhausted {
+                generator: "automata room builder",
+                attempts: 5
+            }),
+            result.map(|r| r.tiles)
+        );
+    }
+
+    #[test]
+    fn rejects_too_small_rooms() {
+        let sut = AutomataRoomBuilder {
+            rows: 2,
+            ..Default::default()
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+
+        let result = sut.try_create_room(&mut rng, &FloorRoom::default());
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
     #[test]
     fn iterate_over_tiles() {
         run_test(|fixture| {
@@ -234,6 +306,7 @@ mod test {
                 cols: 3,
                 wall_percent: 0,
                 iterations: 10,
+                max_attempts: 10,
             },
         }
     }
@@ -0,0 +1,429 @@
+use std::cmp::{max, min};
+
+use rand::{Rng, RngCore};
+
+use crate::{direction::Direction3D, error::GenerationError, floor::floor_architecture::FloorRoom};
+
+use super::{
+    math::{UPosition, URect},
+    room::DungeonRoom,
+    room_builder::RoomBuilder,
+    tile::DungeonTile,
+};
+
+/// Builds rooms by binary space partitioning.
+/// The inner area of the room is split recursively into leaves, until either the leaves are too small
+/// to be split again or the maximum depth is reached. Each leaf gets a randomly sized chamber,
+/// and the chambers of sibling leaves are linked with corridors.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct BspRoomBuilder {
+    pub rows: usize,
+    pub cols: usize,
+    pub min_leaf_size: usize,
+    pub max_depth: u8,
+}
+
+impl Default for BspRoomBuilder {
+    fn default() -> Self {
+        Self {
+            rows: 16,
+            cols: 16,
+            min_leaf_size: 5,
+            max_depth: 4,
+        }
+    }
+}
+
+impl RoomBuilder for BspRoomBuilder {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create bsp room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        if self.rows < 3 || self.cols < 3 {
+            return Err(GenerationError::InvalidConfig(
+                "bsp rooms need at least 3 rows and columns".to_string(),
+            ));
+        }
+
+        let mut room = DungeonRoom {
+            tiles: vec![DungeonTile::Wall; self.rows * self.cols],
+            columns: self.cols,
+            rows: self.rows,
+            ..Default::default()
+        };
+
+        let area = URect::new(1, self.rows - 2, 1, self.cols - 2);
+        let chambers = self.partition(rng, &mut room, area, 0);
+        self.set_exits(&mut room, &room_config.exits, &chambers);
+        room.pathing();
+        room.stair_down = room_config.stair_down;
+        room.stair_up = room_config.stair_up;
+
+        Ok(room)
+    }
+
+    fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    fn get_cols(&self) -> usize {
+        self.cols
+    }
+}
+
+impl BspRoomBuilder {
+    // Splits the area into two leaves, carves both of them and links them with a corridor.
+    // Returns all chambers carved within the area.
+    fn partition(
+        &self,
+        rng: &mut dyn RngCore,
+        room: &mut DungeonRoom,
+        area: URect,
+        depth: u8,
+    ) -> Vec<URect> {
+        let split = if depth < self.max_depth {
+            self.split(rng, &area)
+        } else {
+            None
+        };
+
+        match split {
+            None => {
+                let chamber = self.create_chamber(rng, &area);
+                self.fill(room, &chamber);
+                vec![chamber]
+            }
+            Some((first, second)) => {
+                let mut first_chambers = self.partition(rng, room, first, depth + 1);
+                let mut second_chambers = self.partition(rng, room, second, depth + 1);
+
+                let (from, to) = closest_chambers(&first_chambers, &second_chambers);
+                self.connect(rng, room, from.center(), to.center());
+
+                first_chambers.append(&mut second_chambers);
+                first_chambers
+            }
+        }
+    }
+
+    // Splits an area either along a row or along a column, if the resulting leaves are large enough
+    fn split(&self, rng: &mut dyn RngCore, area: &URect) -> Option<(URect, URect)> {
+        let min_leaf = self.min_leaf_size.max(3);
+        let height = area.row2 - area.row1 + 1;
+        let width = area.col2 - area.col1 + 1;
+        let can_split_rows = height >= min_leaf * 2;
+        let can_split_cols = width >= min_leaf * 2;
+
+        let split_rows = match (can_split_rows, can_split_cols) {
+            (false, false) => return None,
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => {
+                if height * 4 > width * 5 {
+                    true
+                } else if width * 4 > height * 5 {
+                    false
+                } else {
+                    rng.gen_bool(0.5)
+                }
+            }
+        };
+
+        if split_rows {
+            let at = area.row1 + rng.gen_range(min_leaf..=height - min_leaf);
+            Some((
+                URect::new(area.row1, at - 1, area.col1, area.col2),
+                URect::new(at, area.row2, area.col1, area.col2),
+            ))
+        } else {
+            let at = area.col1 + rng.gen_range(min_leaf..=width - min_leaf);
+            Some((
+                URect::new(area.row1, area.row2, area.col1, at - 1),
+                URect::new(area.row1, area.row2, at, area.col2),
+            ))
+        }
+    }
+
+    // Places a chamber inside of the leaf.
+    // The last row and column of the leaf are always left as walls, so chambers of neighboring leaves do not merge.
+    fn create_chamber(&self, rng: &mut dyn RngCore, leaf: &URect) -> URect {
+        let height = leaf.row2 - leaf.row1;
+        let width = leaf.col2 - leaf.col1;
+        if height == 0 || width == 0 {
+            return *leaf;
+        }
+
+        let chamber_height = rng.gen_range((height / 2).max(1)..=height);
+        let chamber_width = rng.gen_range((width / 2).max(1)..=width);
+        let row = leaf.row1 + rng.gen_range(0..=height - chamber_height);
+        let col = leaf.col1 + rng.gen_range(0..=width - chamber_width);
+
+        URect::new(row, row + chamber_height - 1, col, col + chamber_width - 1)
+    }
+
+    fn fill(&self, room: &mut DungeonRoom, chamber: &URect) {
+        for row in chamber.rows() {
+            for col in chamber.cols() {
+                let idx = room.room_idx(row, col);
+                room.tiles[idx] = DungeonTile::Floor;
+            }
+        }
+    }
+
+    fn connect(
+        &self,
+        rng: &mut dyn RngCore,
+        room: &mut DungeonRoom,
+        from: UPosition,
+        to: UPosition,
+    ) {
+        if rng.gen_bool(0.5) {
+            self.apply_horizontal_tunnel(room, from.col, to.col, from.row);
+            self.apply_vertical_tunnel(room, from.row, to.row, to.col);
+        } else {
+            self.apply_vertical_tunnel(room, from.row, to.row, from.col);
+            self.apply_horizontal_tunnel(room, from.col, to.col, to.row);
+        }
+    }
+
+    fn apply_vertical_tunnel(&self, room: &mut DungeonRoom, row1: usize, row2: usize, col: usize) {
+        for row in min(row1, row2)..=max(row1, row2) {
+            let idx = room.room_idx(row, col);
+            room.tiles[idx] = DungeonTile::Floor;
+        }
+    }
+
+    fn apply_horizontal_tunnel(
+        &self,
+        room: &mut DungeonRoom,
+        col1: usize,
+        col2: usize,
+        row: usize,
+    ) {
+        for col in min(col1, col2)..=max(col1, col2) {
+            let idx = room.room_idx(row, col);
+            room.tiles[idx] = DungeonTile::Floor;
+        }
+    }
+
+    // Places an exit tile at the center of each requested side, and digs a corridor from it to the closest chamber
+    fn set_exits(&self, room: &mut DungeonRoom, exits: &[Direction3D], chambers: &[URect]) {
+        for direction in exits {
+            let exit = match direction {
+                Direction3D::Top => UPosition::new(0, self.cols / 2),
+                Direction3D::Bottom => UPosition::new(self.rows - 1, self.cols / 2),
+                Direction3D::Left => UPosition::new(self.rows / 2, 0),
+                Direction3D::Right => UPosition::new(self.rows / 2, self.cols - 1),
+                _ => continue,
+            };
+
+            let target = chambers
+                .iter()
+                .map(|c| c.center())
+                .min_by_key(|c| distance(c, &exit))
+                .unwrap_or(exit);
+
+            // dig away from the border first, so the corridor never runs along the side of the room
+            match direction {
+                Direction3D::Top | Direction3D::Bottom => {
+                    self.apply_vertical_tunnel(room, exit.row, target.row, exit.col);
+                    self.apply_horizontal_tunnel(room, exit.col, target.col, target.row);
+                }
+                _ => {
+                    self.apply_horizontal_tunnel(room, exit.col, target.col, exit.row);
+                    self.apply_vertical_tunnel(room, exit.row, target.row, target.col);
+                }
+            }
+
+            let idx = room.room_idx(exit.row, exit.col);
+            room.tiles[idx] = DungeonTile::Exit;
+        }
+    }
+}
+
+// Finds the pair of chambers with the closest centers, one taken from each list
+fn closest_chambers(first: &[URect], second: &[URect]) -> (URect, URect) {
+    let mut result = (first[0], second[0]);
+    let mut closest = usize::MAX;
+    for a in first {
+        for b in second {
+            let d = distance(&a.center(), &b.center());
+            if d < closest {
+                closest = d;
+                result = (*a, *b);
+            }
+        }
+    }
+
+    result
+}
+
+fn distance(a: &UPosition, b: &UPosition) -> usize {
+    a.row.abs_diff(b.row) + a.col.abs_diff(b.col)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::room::{pathfinding::connected_tile_sets, print::print_room};
+
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn creates_printable_room() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = BspRoomBuilder::default();
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top, Direction3D::Right],
+            ..Default::default()
+        };
+
+        let room = sut.create_room(&mut rng, &room_config);
+
+        let expected_tiles = "########E#######
+##...###.#######
+##...###.#######
+##...#.......###
+##...#.......###
+###.#####.######
+###.#####.######
+###.##........##
+###..#.........E
+###..#........##
+###...........##
+###..#........##
+###..#........##
+###..#........##
+################
+################"
+            .to_string();
+        let room_tile_str = print_room(room.rows, room.columns, room.tiles, 0, 0);
+        assert_eq!(expected_tiles, room_tile_str);
+    }
+
+    #[test]
+    fn opens_only_requested_sides() {
+        let sut = BspRoomBuilder {
+            rows: 21,
+            cols: 31,
+            ..Default::default()
+        };
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Bottom, Direction3D::Left],
+            ..Default::default()
+        };
+
+        for seed in 0..20 {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let room = sut.create_room(&mut rng, &room_config);
+
+            let mut exit_directions = room.exit_directions.clone();
+            exit_directions.sort_by_key(|d| *d as u8);
+            assert_eq!(
+                vec![Direction3D::Bottom, Direction3D::Left],
+                exit_directions
+            );
+            assert_eq!(
+                2,
+                room.tiles
+                    .iter()
+                    .filter(|t| **t == DungeonTile::Exit)
+                    .count()
+            );
+        }
+    }
+
+    #[test]
+    fn connects_all_chambers() {
+        let sut = BspRoomBuilder {
+            rows: 32,
+            cols: 32,
+            min_leaf_size: 4,
+            max_depth: 6,
+        };
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top],
+            ..Default::default()
+        };
+
+        for seed in 0..20 {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let room = sut.create_room(&mut rng, &room_config);
+
+            assert_eq!(1, connected_tile_sets(&room).len());
+        }
+    }
+
+    #[test]
+    fn rejects_too_small_rooms() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = BspRoomBuilder {
+            rows: 2,
+            cols: 2,
+            ..Default::default()
+        };
+
+        let result = sut.try_create_room(&mut rng, &FloorRoom::default());
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn creates_smallest_room() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = BspRoomBuilder {
+            rows: 3,
+            cols: 3,
+            ..Default::default()
+        };
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top, Direction3D::Left],
+            ..Default::default()
+        };
+
+        let room = sut.try_create_room(&mut rng, &room_config).unwrap();
+
+        assert_eq!(
+            "#E#\nE.#\n###",
+            print_room(room.rows, room.columns, room.tiles.clone(), 0, 0)
+        );
+        assert_eq!(1, connected_tile_sets(&room).len());
+    }
+
+    #[test]
+    fn does_not_split_leaves_below_minimum_size() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = BspRoomBuilder::default();
+
+        let result = sut.split(&mut rng, &URect::new(1, 9, 1, 9));
+
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn splits_leaves_along_longer_side() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = BspRoomBuilder::default();
+
+        let (first, second) = sut.split(&mut rng, &URect::new(1, 4, 1, 20)).unwrap();
+
+        assert_eq!((1, 4), (first.row1, first.row2));
+        assert_eq!((1, 4), (second.row1, second.row2));
+        assert_eq!(1, first.col1);
+        assert_eq!(first.col2 + 1, second.col1);
+        assert_eq!(20, second.col2);
+    }
+}
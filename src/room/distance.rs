@@ -0,0 +1,225 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::{
+    pathfinding::{neighbor_steps, Movement},
+    tile::{DungeonTile, TileGrid},
+};
+
+/// Distance of tiles which can not be reached from any source
+pub const UNREACHABLE: u32 = u32::MAX;
+
+/// Walking distances from one or more source tiles to every tile of a grid, calculated with Dijkstra.
+/// Works for anything providing tiles, like single rooms or the stitched map of a whole floor.
+///
+/// Paths move horizontally and vertically only; every step costs the cost of the entered tile.
+/// Tiles which can not be reached have the distance UNREACHABLE.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistanceMap {
+    pub rows: usize,
+    pub columns: usize,
+    pub distances: Vec<u32>,
+}
+
+impl DistanceMap {
+    /// Calculates the distances from the source tiles.
+    /// The cost function returns the cost of entering a tile, or None if the tile can not be entered.
+    /// Sources out of range or on impassable tiles are ignored.
+    pub fn new<G, C>(grid: &G, sources: &[usize], cost: C) -> Self
+    where
+        G: TileGrid,
+        C: Fn(&DungeonTile) -> Option<u32>,
+    {
+        let tiles = grid.grid_tiles();
+        let mut distances = vec![UNREACHABLE; tiles.len()];
+        let mut open = BinaryHeap::new();
+        for source in sources {
+            if *source < tiles.len() && cost(&tiles[*source]).is_some() {
+                distances[*source] = 0;
+                open.push(Reverse((0, *source)));
+            }
+        }
+
+        while let Some(Reverse((distance, current))) = open.pop() {
+            if distance > distances[current] {
+                continue;
+            }
+
+            for (next, _) in neighbor_steps(grid, current, Movement::FourWay, &cost) {
+                let next_distance = match cost(&tiles[next]) {
+                    Some(c) => distance.saturating_add(c),
+                    None => continue,
+                };
+                if next_distance < distances[next] {
+                    distances[next] = next_distance;
+                    open.push(Reverse((next_distance, next)));
+                }
+            }
+        }
+
+        Self {
+            rows: grid.grid_rows(),
+            columns: grid.grid_columns(),
+            distances,
+        }
+    }
+
+    /// Distance of a tile to the closest source; None if the tile is unreachable
+    pub fn distance(&self, idx: usize) -> Option<u32> {
+        match self.distances.get(idx) {
+            Some(d) if *d != UNREACHABLE => Some(*d),
+            _ => None,
+        }
+    }
+
+    /// The reachable tile with the highest distance; on a tie, the tile with the lowest index wins
+    pub fn farthest(&self) -> Option<usize> {
+        self.farthest_where(|_| true)
+    }
+
+    /// The reachable tile with the highest distance, among the tiles accepted by the filter
+    pub fn farthest_where<F: Fn(usize) -> bool>(&self, filter: F) -> Option<usize> {
+        let mut result: Option<usize> = None;
+        for (idx, distance) in self.distances.iter().enumerate() {
+            if *distance == UNREACHABLE || !filter(idx) {
+                continue;
+            }
+
+            if result
+                .map(|r| *distance > self.distances[r])
+                .unwrap_or(true)
+            {
+                result = Some(idx);
+            }
+        }
+
+        result
+    }
+
+    /// Neighboring tile leading towards the closest source, for approaching it.
+    /// Returns None if the tile is a source itself or unreachable.
+    pub fn step_towards(&self, idx: usize) -> Option<usize> {
+        let current = self.distance(idx)?;
+        self.neighbors(idx)
+            .into_iter()
+            .filter(|n| self.distances[*n] < current)
+            .min_by_key(|n| self.distances[*n])
+    }
+
+    /// Neighboring tile leading away from the sources, for fleeing from them.
+    /// Returns None if no neighbor is farther away from the sources than the tile.
+    pub fn step_away(&self, idx: usize) -> Option<usize> {
+        let current = self.distance(idx)?;
+        self.neighbors(idx)
+            .into_iter()
+            .filter(|n| self.distances[*n] != UNREACHABLE && self.distances[*n] > current)
+            .max_by_key(|n| self.distances[*n])
+    }
+
+    fn neighbors(&self, idx: usize) -> Vec<usize> {
+        let row = idx / self.columns;
+        let col = idx % self.columns;
+        let mut result = vec![];
+        if row > 0 {
+            result.push(idx - self.columns);
+        }
+        if col > 0 {
+            result.push(idx - 1);
+        }
+        if col < self.columns - 1 {
+            result.push(idx + 1);
+        }
+        if row < self.rows - 1 {
+            result.push(idx + self.columns);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::room::{pathfinding::walking_cost, print::parse_room};
+
+    use super::*;
+
+    #[test]
+    fn calculates_distances_from_source() {
+        // ...
+        // .#.
+        // ...
+        let room = parse_room("...\n.#.\n...").unwrap();
+
+        let result = DistanceMap::new(&room, &[0], walking_cost);
+
+        assert_eq!(vec![0, 1, 2, 1, UNREACHABLE, 3, 2, 3, 4], result.distances);
+        assert_eq!(Some(8), result.farthest());
+        assert_eq!(None, result.distance(4));
+    }
+
+    #[test]
+    fn calculates_distances_from_closest_of_multiple_sources() {
+        let room = parse_room(".....").unwrap();
+
+        let result = DistanceMap::new(&room, &[0, 4], walking_cost);
+
+        assert_eq!(vec![0, 1, 2, 1, 0], result.distances);
+        assert_eq!(Some(2), result.farthest());
+    }
+
+    #[test]
+    fn marks_separated_tiles_unreachable() {
+        let room = parse_room("..#..").unwrap();
+
+        let result = DistanceMap::new(&room, &[0], walking_cost);
+
+        assert_eq!(
+            vec![0, 1, UNREACHABLE, UNREACHABLE, UNREACHABLE],
+            result.distances
+        );
+        assert_eq!(Some(1), result.farthest());
+    }
+
+    #[test]
+    fn ignores_sources_on_walls() {
+        let room = parse_room("#..").unwrap();
+
+        let result = DistanceMap::new(&room, &[0], walking_cost);
+
+        assert_eq!(None, result.farthest());
+    }
+
+    #[test]
+    fn weighs_distances_with_tile_costs() {
+        let room = parse_room(".E.").unwrap();
+        let cost = |t: &DungeonTile| match t {
+            DungeonTile::Exit => Some(5),
+            DungeonTile::Wall => None,
+            _ => Some(1),
+        };
+
+        let result = DistanceMap::new(&room, &[0], cost);
+
+        assert_eq!(vec![0, 5, 6], result.distances);
+    }
+
+    #[test]
+    fn finds_farthest_tile_matching_filter() {
+        let room = parse_room("....").unwrap();
+
+        let result = DistanceMap::new(&room, &[0], walking_cost);
+
+        assert_eq!(Some(2), result.farthest_where(|idx| idx != 3));
+    }
+
+    #[test]
+    fn steps_towards_and_away_from_sources() {
+        let room = parse_room("...\n.#.\n...").unwrap();
+        let result = DistanceMap::new(&room, &[0], walking_cost);
+
+        assert_eq!(Some(2), result.step_towards(5));
+        assert_eq!(None, result.step_towards(0));
+        assert_eq!(Some(8), result.step_away(5));
+        assert_eq!(None, result.step_away(8));
+    }
+}
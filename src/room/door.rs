@@ -0,0 +1,247 @@
+use rand::{Rng, RngCore};
+
+use crate::{error::GenerationError, floor::floor_architecture::FloorRoom};
+
+use super::{
+    room::DungeonRoom,
+    room_builder::RoomBuilder,
+    tile::{DungeonTile, TileGrid},
+};
+
+/// Settings for placing doors where corridors enter chambers
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DoorConfig {
+    /// Percentage of the doorways which get a door
+    pub door_percent: u8,
+    /// Percentage of the placed doors which are secret doors
+    pub secret_percent: u8,
+}
+
+impl Default for DoorConfig {
+    fn default() -> Self {
+        Self {
+            door_percent: 100,
+            secret_percent: 0,
+        }
+    }
+}
+
+/// Floor tiles where a corridor enters a chamber.
+/// A doorway is a chokepoint with walls on two opposite sides and walkable tiles on the other two sides,
+/// of which at least one belongs to an open area.
+/// Tiles on the border of the grid are never doorways.
+pub fn find_doorways<G: TileGrid>(grid: &G) -> Vec<usize> {
+    let (rows, cols) = (grid.grid_rows() as i32, grid.grid_columns() as i32);
+    let tiles = grid.grid_tiles();
+    let walkable = |row: i32, col: i32| {
+        row >= 0
+            && col >= 0
+            && row < rows
+            && col < cols
+            && tiles[(row * cols + col) as usize].is_walkable()
+    };
+    // tiles with walkable tiles all around them, besides the ones of a corridor
+    let open = |row: i32, col: i32| {
+        let neighbors = (-1..=1)
+            .flat_map(|dr| (-1..=1).map(move |dc| (dr, dc)))
+            .filter(|(dr, dc)| (*dr, *dc) != (0, 0) && walkable(row + dr, col + dc))
+            .count();
+        walkable(row, col) && neighbors >= 4
+    };
+
+    let mut result = vec![];
+    for row in 1..rows - 1 {
+        for col in 1..cols - 1 {
+            if tiles[(row * cols + col) as usize] != DungeonTile::Floor {
+                continue;
+            }
+
+            let horizontal_passage = !walkable(row - 1, col)
+                && !walkable(row + 1, col)
+                && walkable(row, col - 1)
+                && walkable(row, col + 1)
+                && (open(row, col - 1) || open(row, col + 1));
+            let vertical_passage = !walkable(row, col - 1)
+                && !walkable(row, col + 1)
+                && walkable(row - 1, col)
+                && walkable(row + 1, col)
+                && (open(row - 1, col) || open(row + 1, col));
+
+            if horizontal_passage || vertical_passage {
+                result.push((row * cols + col) as usize);
+            }
+        }
+    }
+
+    result
+}
+
+/// Places doors on the doorways of the room.
+/// Doorways next to an already placed door are skipped, so corridors get a single door at each end.
+pub fn place_doors(rng: &mut dyn RngCore, room: &mut DungeonRoom, config: &DoorConfig) {
+    for idx in find_doorways(room) {
+        let next_to_door = [
+            idx.checked_sub(room.columns),
+            idx.checked_sub(1),
+            Some(idx + 1),
+            Some(idx + room.columns),
+        ]
+        .iter()
+        .flatten()
+        .any(|n| room.tiles.get(*n).is_some_and(|t| t.is_door()));
+
+        if next_to_door || rng.gen_range(0..100) >= config.door_percent {
+            continue;
+        }
+
+        room.tiles[idx] = if rng.gen_range(0..100) < config.secret_percent {
+            DungeonTile::SecretDoor
+        } else {
+            DungeonTile::Door
+        };
+    }
+}
+
+/// Places doors into the rooms of another room builder
+pub struct DoorRoomBuilder {
+    pub builder: Box<dyn RoomBuilder>,
+    pub config: DoorConfig,
+}
+
+impl DoorRoomBuilder {
+    pub fn new(builder: Box<dyn RoomBuilder>, config: DoorConfig) -> Self {
+        Self { builder, config }
+    }
+}
+
+impl RoomBuilder for DoorRoomBuilder {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        let mut room = self.builder.create_room(rng, room_config);
+        place_doors(rng, &mut room, &self.config);
+
+        room
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        let mut room = self.builder.try_create_room(rng, room_config)?;
+        place_doors(rng, &mut room, &self.config);
+
+        Ok(room)
+    }
+
+    fn get_rows(&self) -> usize {
+        self.builder.get_rows()
+    }
+
+    fn get_cols(&self) -> usize {
+        self.builder.get_cols()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    use crate::{
+        direction::Direction3D,
+        room::{
+            grid::GridRoomBuilder,
+            print::{parse_room, print_room},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn places_doors_where_corridors_enter_chambers() {
+        let mut room = parse_room(
+            "#########\n\
+             #...#...#\n\
+             #.......#\n\
+             #...#...#\n\
+             ###.#####\n\
+             ###.#####\n\
+             #.....###\n\
+             #.....###\n\
+             #########",
+        )
+        .unwrap();
+
+        place_doors(
+            &mut Pcg64::seed_from_u64(1),
+            &mut room,
+            &DoorConfig::default(),
+        );
+
+        assert_eq!(
+            "#########\n\
+             #...#...#\n\
+             #...+...#\n\
+             #...#...#\n\
+             ###+#####\n\
+             ###.#####\n\
+             #.....###\n\
+             #.....###\n\
+             #########",
+            print_room(room.rows, room.columns, room.tiles, 0, 0)
+        );
+    }
+
+    #[test]
+    fn places_no_doors_in_corridors_and_open_areas() {
+        let input = "#######\n\
+                     #.....#\n\
+                     ###.###\n\
+                     ###...#\n\
+                     #######";
+        let mut room = parse_room(input).unwrap();
+
+        place_doors(
+            &mut Pcg64::seed_from_u64(1),
+            &mut room,
+            &DoorConfig::default(),
+        );
+
+        assert_eq!(input, print_room(room.rows, room.columns, room.tiles, 0, 0));
+    }
+
+    #[test]
+    fn places_secret_doors() {
+        let mut room = parse_room("#######\n#..#..#\n#.....#\n#..#..#\n#######").unwrap();
+        let config = DoorConfig {
+            secret_percent: 100,
+            ..Default::default()
+        };
+
+        place_doors(&mut Pcg64::seed_from_u64(1), &mut room, &config);
+
+        assert_eq!(DungeonTile::SecretDoor, room.tiles[17]);
+    }
+
+    #[test]
+    fn keeps_pathing_of_room_with_doors() {
+        let builder =
+            DoorRoomBuilder::new(Box::new(GridRoomBuilder::default()), DoorConfig::default());
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Left, Direction3D::Right],
+            ..Default::default()
+        };
+
+        let mut room = builder.create_room(&mut Pcg64::seed_from_u64(3), &room_config);
+        let pathing = room.pathing.clone();
+        room.pathing();
+
+        assert!(room.tiles.contains(&DungeonTile::Door));
+        assert_eq!(pathing, room.pathing);
+    }
+}
@@ -1,27 +1,62 @@
 use rand::prelude::*;
-use rand_pcg::Pcg64;
 
-use crate::{direction::Direction3D, floor::floor_architecture::FloorRoom};
+use crate::{direction::Direction3D, error::GenerationError, floor::floor_architecture::FloorRoom};
 
 use super::{room::DungeonRoom, room_builder::RoomBuilder, tile::DungeonTile};
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     FindExits,
     ReverseCenter,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct DrunkardRoomBuilder {
     pub rows: usize,
     pub cols: usize,
     pub iterations: u8,
     pub steps: u8,
     pub mode: Mode,
+    /// Number of digging runs, until the digger has to have dug out to every required exit side
+    pub max_attempts: u16,
+}
+
+impl Default for DrunkardRoomBuilder {
+    fn default() -> Self {
+        Self {
+            rows: 16,
+            cols: 16,
+            iterations: 8,
+            steps: 30,
+            mode: Mode::FindExits,
+            max_attempts: 1000,
+        }
+    }
 }
 
 impl RoomBuilder for DrunkardRoomBuilder {
-    fn create_room(&self, rng: &mut Pcg64, room_config: &FloorRoom) -> DungeonRoom {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create drunkard room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        if self.rows < 3 || self.cols < 3 {
+            return Err(GenerationError::InvalidConfig(
+                "drunkard rooms need at least 3 rows and columns".to_string(),
+            ));
+        }
+
         let default_tile_type = match self.mode {
             Mode::FindExits => DungeonTile::Wall,
             Mode::ReverseCenter => DungeonTile::Floor,
@@ -39,10 +74,17 @@ impl RoomBuilder for DrunkardRoomBuilder {
 
         let mut exits_hit: Vec<Direction3D> = vec![];
         let mut all_exits_hit = false;
-        let mut iters = 0;
+        let mut iters: u16 = 0;
 
         // the digger needs to hit exits on all relevant sides; it continues to dig until it has dug out to every side we need
-        while !all_exits_hit || iters < self.iterations {
+        while !all_exits_hit || iters < self.iterations as u16 {
+            if !all_exits_hit && iters >= self.max_attempts {
+                return Err(GenerationError::AttemptsExhausted {
+                    generator: "drunkard room builder",
+                    attempts: iters as usize,
+                });
+            }
+
             iters += 1;
             self.drunkard(next_start_point, rng, &mut room);
             exits_hit = self.get_hit_exits(&room);
@@ -57,7 +99,7 @@ impl RoomBuilder for DrunkardRoomBuilder {
             room.close_side(*non_wanted_exit_direction);
         }
 
-        room
+        Ok(room)
     }
 
     fn get_cols(&self) -> usize {
@@ -70,20 +112,16 @@ impl RoomBuilder for DrunkardRoomBuilder {
 }
 
 impl DrunkardRoomBuilder {
-    fn drunkard(&self, start: (usize, usize), rng: &mut Pcg64, room: &mut DungeonRoom) {
+    fn drunkard(&self, start: (usize, usize), rng: &mut dyn RngCore, room: &mut DungeonRoom) {
         let next_start = start.clone();
         let mut drunkard_pos = (next_start.0 as i32, next_start.1 as i32);
-        let mut distance_staggered = 0;
+        let mut distance_staggered: u16 = 0;
         let dug_tile = match self.mode {
             Mode::FindExits => DungeonTile::Floor,
             Mode::ReverseCenter => DungeonTile::Wall,
         };
 
         loop {
-            if drunkard_pos.0 < 0 || drunkard_pos.1 < 0 {
-                panic!("dunkard has negative coordinates")
-            }
-
             let drunk_idx = room.room_idx(drunkard_pos.0 as usize, drunkard_pos.1 as usize);
             room.tiles[drunk_idx] = dug_tile;
             match rng.gen_range(0..4) {
@@ -105,41 +143,12 @@ impl DrunkardRoomBuilder {
             }
 
             distance_staggered += 1;
-            if distance_staggered > self.steps {
+            if distance_staggered > self.steps as u16 {
                 break;
             }
         }
     }
 
-    fn get_hit_exits(&self, room: &DungeonRoom) -> Vec<Direction3D> {
-        let mut directions = vec![];
-        for (row, col) in room
-            .tiles
-            .iter()
-            .enumerate()
-            .filter(|(_, t)| **t == DungeonTile::Floor)
-            .map(|(idx, _)| (room.row(idx), room.col(idx)))
-        {
-            if row == self.rows - 1 && !directions.contains(&Direction3D::Top) {
-                directions.push(Direction3D::Top);
-            }
-
-            if row == 0 && !directions.contains(&Direction3D::Bottom) {
-                directions.push(Direction3D::Bottom);
-            }
-
-            if col == self.cols - 1 && !directions.contains(&Direction3D::Right) {
-                directions.push(Direction3D::Right);
-            }
-
-            if col == 0 && !directions.contains(&Direction3D::Left) {
-                directions.push(Direction3D::Left);
-            }
-        }
-
-        return directions;
-    }
-
     fn calculate_next_start_point(
         &self,
         room: &DungeonRoom,
@@ -157,7 +166,7 @@ impl DrunkardRoomBuilder {
                 .tiles
                 .iter()
                 .enumerate()
-                .filter(|(_, t)| **t == DungeonTile::Floor)
+                .filter(|(_, t)| **t == DungeonTile::Floor || t.is_door())
                 .map(|(idx, _)| idx);
             let result = match direction {
                 Direction3D::Top => floors.max_by_key(|idx| room.row(*idx)),
@@ -182,6 +191,8 @@ impl DrunkardRoomBuilder {
 mod test {
     use crate::room::print::print_room;
 
+    use rand_pcg::Pcg64;
+
     use super::*;
 
     #[test]
@@ -208,6 +219,30 @@ mod test {
         assert_eq!((2, 2), result);
     }
 
+    #[test]
+    fn counts_doors_as_dug_tiles() {
+        let mut sut = create_sut();
+        sut.rows = 5;
+        sut.cols = 5;
+        let mut room = DungeonRoom {
+            tiles: vec![DungeonTile::Wall; 25],
+            rows: 5,
+            columns: 5,
+            ..Default::default()
+        };
+        room.tiles[12] = DungeonTile::Floor;
+        room.tiles[17] = DungeonTile::Door;
+
+        let exits_hit = sut.get_hit_exits(&room);
+        let result = sut.calculate_next_start_point(&room, &exits_hit, &vec![Direction3D::Top]);
+
+        assert!(exits_hit.is_empty());
+        assert_eq!((3, 2), result);
+
+        room.tiles[22] = DungeonTile::SecretDoor;
+        assert_eq!(vec![Direction3D::Top], sut.get_hit_exits(&room));
+    }
+
     #[test]
     fn defaults_to_center_if_no_exit_needs_to_be_hit() {
         // arrange
@@ -307,6 +342,30 @@ mod test {
         );
     }
 
+    #[test]
+    fn fails_if_exits_are_not_hit_within_attempts() {
+        let sut = DrunkardRoomBuilder {
+            steps: 0,
+            max_attempts: 5,
+            ..Default::default()
+        };
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top],
+            ..Default::default()
+        };
+        let mut rng = Pcg64::seed_from_u64(1);
+
+        let result = sut.try_create_room(&mut rng, &room_config);
+
+        assert_eq!(
+            Err(GenerationError::AttemptsExhausted {
+                generator: "drunkard room builder",
+                attempts: 5
+            }),
+            result.map(|r| r.tiles)
+        );
+    }
+
     #[test]
     fn eliminates_wall_tiles() {
         let mut rng = Pcg64::seed_from_u64(1);
@@ -334,6 +393,7 @@ mod test {
             iterations: 2,
             steps: 2,
             mode: Mode::FindExits,
+            max_attempts: 1000,
         }
     }
 }
@@ -1,7 +1,6 @@
-use rand::Rng;
-use rand_pcg::Pcg64;
+use rand::{Rng, RngCore};
 
-use crate::{direction::Direction3D, floor::floor_architecture::FloorRoom};
+use crate::{direction::Direction3D, error::GenerationError, floor::floor_architecture::FloorRoom};
 
 use super::{
     math::{Dimension, URect},
@@ -16,6 +15,12 @@ pub enum Alignment {
     Horizontally,
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct GridRoomBuilder {
     pub rect_size: Dimension,
     pub rects: Dimension,
@@ -31,7 +36,27 @@ impl Default for GridRoomBuilder {
 }
 
 impl RoomBuilder for GridRoomBuilder {
-    fn create_room(&self, rng: &mut Pcg64, room_config: &FloorRoom) -> DungeonRoom {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create grid room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        // a grid of at least one rect of at least one tile always has 3 rows and columns
+        if [self.rect_size, self.rects]
+            .iter()
+            .any(|d| d.vertical == 0 || d.horizontal == 0)
+        {
+            return Err(GenerationError::InvalidConfig(format!(
+                "grid rooms need at least one rect of at least one tile, got {:?} rects of size {:?}",
+                self.rects, self.rect_size
+            )));
+        }
+
         let exits = &room_config.exits;
         let rects = self.create_rects();
         let mut room = self.room_from_rects(rng, &rects);
@@ -40,7 +65,7 @@ impl RoomBuilder for GridRoomBuilder {
         room.stair_down = room_config.stair_down;
         room.stair_up = room_config.stair_up;
 
-        room
+        Ok(room)
     }
 
     fn get_rows(&self) -> usize {
@@ -71,7 +96,7 @@ impl GridRoomBuilder {
         rects
     }
 
-    fn room_from_rects(&self, rng: &mut Pcg64, rects: &Vec<URect>) -> DungeonRoom {
+    fn room_from_rects(&self, rng: &mut dyn RngCore, rects: &Vec<URect>) -> DungeonRoom {
         let mut room = DungeonRoom {
             tiles: vec![DungeonTile::Wall; (self.get_cols() * self.get_rows()) as usize],
             columns: self.get_cols(),
@@ -96,7 +121,7 @@ impl GridRoomBuilder {
         }
     }
 
-    fn connect(&self, room: &mut DungeonRoom, rects: &Vec<URect>, rng: &mut Pcg64) {
+    fn connect(&self, room: &mut DungeonRoom, rects: &Vec<URect>, rng: &mut dyn RngCore) {
         let align = match rng.gen_range(0..2) {
             0 => Alignment::Vertically,
             _ => Alignment::Horizontally,
@@ -141,7 +166,7 @@ impl GridRoomBuilder {
         }
     }
 
-    fn find_doorway_connections(&self, rng: &mut Pcg64, align: &Alignment) -> Vec<usize> {
+    fn find_doorway_connections(&self, rng: &mut dyn RngCore, align: &Alignment) -> Vec<usize> {
         let doorways_amount = match align {
             Alignment::Vertically => self.rects.vertical - 1,
             Alignment::Horizontally => self.rects.horizontal - 1,
@@ -220,9 +245,24 @@ impl GridRoomBuilder {
 mod test {
     use crate::room::{math::UPosition, print::print_room};
 
+    use rand_pcg::Pcg64;
+
     use super::*;
     use rand::prelude::*;
 
+    #[test]
+    fn rejects_empty_rects() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = GridRoomBuilder {
+            rect_size: Dimension::new(0, 3),
+            ..Default::default()
+        };
+
+        let result = sut.try_create_room(&mut rng, &FloorRoom::default());
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
     #[test]
     fn creates_dungeon_room() {
         let mut rng = Pcg64::seed_from_u64(1);
@@ -0,0 +1,379 @@
+use rand::{Rng, RngCore};
+
+use crate::{error::GenerationError, floor::floor_architecture::FloorRoom};
+
+use super::{
+    pathfinding::{connected_tile_sets, find_path, Movement},
+    room::DungeonRoom,
+    room_builder::RoomBuilder,
+    tile::DungeonTile,
+};
+
+// crossing a hazard tile costs more than walking around it, so bridges stay short
+const HAZARD_CROSSING_COST: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hazard {
+    #[default]
+    Water,
+    Lava,
+    Chasm,
+}
+
+impl Hazard {
+    pub fn tile(&self) -> DungeonTile {
+        match self {
+            Hazard::Water => DungeonTile::Water,
+            Hazard::Lava => DungeonTile::Lava,
+            Hazard::Chasm => DungeonTile::Chasm,
+        }
+    }
+}
+
+/// Settings for painting rivers and pools of a hazard onto the floor tiles of a room
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct HazardConfig {
+    pub hazard: Hazard,
+    /// Number of rivers, each crossing the room from one side to the opposite side
+    pub rivers: usize,
+    pub river_width: usize,
+    /// Percentage of floor tiles seeding pools, before the pools are shaped with cellular automata
+    pub pool_percent: u8,
+    pub pool_iterations: u8,
+}
+
+impl Default for HazardConfig {
+    fn default() -> Self {
+        Self {
+            hazard: Hazard::Water,
+            rivers: 1,
+            river_width: 1,
+            pool_percent: 40,
+            pool_iterations: 4,
+        }
+    }
+}
+
+/// Paints rivers and pools of the configured hazard onto the floor tiles of the room.
+///
+/// Exit tiles, stairs and the exits of the pathing are never painted over.
+/// Wherever the hazard separates them from each other, bridges are laid across it,
+/// so all of them stay connected if they were connected before.
+/// Pathing and exits of the room are recalculated afterwards.
+pub fn add_hazards(rng: &mut dyn RngCore, room: &mut DungeonRoom, config: &HazardConfig) {
+    if room.rows < 3 || room.columns < 3 {
+        return;
+    }
+
+    let protected = protected_tiles(room);
+    for _ in 0..config.rivers {
+        paint_river(rng, room, config, &protected);
+    }
+    paint_pools(rng, room, config, &protected);
+    add_bridges(room, &protected);
+
+    room.pathing();
+}
+
+// tiles which have to stay walkable and connected: exits, stairs and the border tiles of the pathing
+fn protected_tiles(room: &DungeonRoom) -> Vec<usize> {
+    let mut result: Vec<usize> = room
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| {
+            matches!(
+                t,
+                DungeonTile::Exit | DungeonTile::StairsUp | DungeonTile::StairsDown
+            )
+        })
+        .map(|(idx, _)| idx)
+        .chain(room.exits.iter().copied())
+        .collect();
+    result.sort();
+    result.dedup();
+
+    result
+}
+
+fn paint(room: &mut DungeonRoom, idx: usize, tile: DungeonTile, protected: &[usize]) {
+    if room.tiles[idx] == DungeonTile::Floor && !protected.contains(&idx) {
+        room.tiles[idx] = tile;
+    }
+}
+
+// a random walk between two opposite sides, drifting sideways by at most one tile per step
+fn paint_river(
+    rng: &mut dyn RngCore,
+    room: &mut DungeonRoom,
+    config: &HazardConfig,
+    protected: &[usize],
+) {
+    let horizontal = rng.gen_bool(0.5);
+    let (length, breadth) = if horizontal {
+        (room.columns, room.rows)
+    } else {
+        (room.rows, room.columns)
+    };
+
+    let mut offset = rng.gen_range(1..breadth - 1);
+    for step in 0..length {
+        for across in offset..(offset + config.river_width).min(breadth - 1) {
+            let idx = if horizontal {
+                room.room_idx(across, step)
+            } else {
+                room.room_idx(step, across)
+            };
+            paint(room, idx, config.hazard.tile(), protected);
+        }
+
+        offset = match rng.gen_range(0..3) {
+            0 => offset.saturating_sub(1).max(1),
+            1 => (offset + 1).min(breadth - 2),
+            _ => offset,
+        };
+    }
+}
+
+// seeds the floor randomly and grows the seeds into pools, like the automata room builder grows walls
+fn paint_pools(
+    rng: &mut dyn RngCore,
+    room: &mut DungeonRoom,
+    config: &HazardConfig,
+    protected: &[usize],
+) {
+    let candidates: Vec<usize> = (0..room.tiles.len())
+        .filter(|idx| room.tiles[*idx] == DungeonTile::Floor && !protected.contains(idx))
+        .collect();
+
+    let mut pool = vec![false; room.tiles.len()];
+    for idx in candidates.iter() {
+        pool[*idx] = rng.gen_range(0..100) < config.pool_percent;
+    }
+
+    for _ in 0..config.pool_iterations {
+        let mut next = pool.clone();
+        for idx in candidates.iter() {
+            let (row, col) = (room.row(*idx) as i32, room.col(*idx) as i32);
+            let neighbors = (-1..=1)
+                .flat_map(|dr| (-1..=1).map(move |dc| (row + dr, col + dc)))
+                .filter(|(r, c)| (*r, *c) != (row, col) && room.in_bounds(*r, *c))
+                .filter(|(r, c)| pool[room.room_idx(*r as usize, *c as usize)])
+                .count();
+            next[*idx] = neighbors >= 4 || (pool[*idx] && neighbors >= 3);
+        }
+        pool = next;
+    }
+
+    for idx in candidates {
+        if pool[idx] {
+            paint(room, idx, config.hazard.tile(), protected);
+        }
+    }
+}
+
+// connects every protected tile with the first one, laying bridges over the cheapest crossing of hazard tiles
+fn add_bridges(room: &mut DungeonRoom, protected: &[usize]) {
+    let first = match protected.first() {
+        Some(first) => *first,
+        None => return,
+    };
+
+    for target in protected.iter().skip(1) {
+        let connected = connected_tile_sets(room)
+            .iter()
+            .any(|set| set.contains(&first) && set.contains(target));
+        if connected {
+            continue;
+        }
+
+        let crossing_cost = |tile: &DungeonTile| {
+            if tile.is_hazard() {
+                Some(HAZARD_CROSSING_COST)
+            } else if tile.is_walkable() {
+                Some(1)
+            } else {
+                None
+            }
+        };
+        // tiles cut off by walls were never connected, so they need no bridge
+        if let Some(path) = find_path(room, first, *target, Movement::FourWay, crossing_cost) {
+            for idx in path {
+                if room.tiles[idx].is_hazard() {
+                    room.tiles[idx] = DungeonTile::Bridge;
+                }
+            }
+        }
+    }
+}
+
+/// Adds hazards to the rooms of another room builder
+pub struct HazardRoomBuilder {
+    pub builder: Box<dyn RoomBuilder>,
+    pub config: HazardConfig,
+}
+
+impl HazardRoomBuilder {
+    pub fn new(builder: Box<dyn RoomBuilder>, config: HazardConfig) -> Self {
+        Self { builder, config }
+    }
+}
+
+impl RoomBuilder for HazardRoomBuilder {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        let mut room = self.builder.create_room(rng, room_config);
+        add_hazards(rng, &mut room, &self.config);
+
+        room
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        let mut room = self.builder.try_create_room(rng, room_config)?;
+        add_hazards(rng, &mut room, &self.config);
+
+        Ok(room)
+    }
+
+    fn get_rows(&self) -> usize {
+        self.builder.get_rows()
+    }
+
+    fn get_cols(&self) -> usize {
+        self.builder.get_cols()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    use crate::{
+        direction::Direction3D,
+        room::{automata::AutomataRoomBuilder, print::parse_room},
+    };
+
+    use super::*;
+
+    #[test]
+    fn paints_river_across_room() {
+        let mut room = open_room();
+        let config = HazardConfig {
+            pool_percent: 0,
+            ..Default::default()
+        };
+
+        add_hazards(&mut Pcg64::seed_from_u64(1), &mut room, &config);
+
+        let crossing = room
+            .tiles
+            .iter()
+            .filter(|t| **t == DungeonTile::Water || **t == DungeonTile::Bridge)
+            .count();
+        assert!(crossing >= 5);
+        assert_connected(&room);
+    }
+
+    #[test]
+    fn bridges_hazards_between_exits() {
+        let mut room = parse_room(
+            "#####\n\
+             E...E\n\
+             #####",
+        )
+        .unwrap();
+        room.pathing();
+        let config = HazardConfig {
+            hazard: Hazard::Lava,
+            rivers: 0,
+            pool_percent: 100,
+            pool_iterations: 0,
+            ..Default::default()
+        };
+
+        add_hazards(&mut Pcg64::seed_from_u64(1), &mut room, &config);
+
+        assert_eq!(
+            vec![
+                DungeonTile::Exit,
+                DungeonTile::Bridge,
+                DungeonTile::Bridge,
+                DungeonTile::Bridge,
+                DungeonTile::Exit
+            ],
+            room.tiles[5..10].to_vec()
+        );
+    }
+
+    #[test]
+    fn keeps_exits_and_stairs_connected() {
+        let builder = HazardRoomBuilder::new(
+            Box::new(AutomataRoomBuilder::default()),
+            HazardConfig {
+                hazard: Hazard::Chasm,
+                rivers: 2,
+                river_width: 2,
+                ..Default::default()
+            },
+        );
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Left, Direction3D::Right, Direction3D::Top],
+            ..Default::default()
+        };
+
+        for seed in 0..10 {
+            let room = builder.create_room(&mut Pcg64::seed_from_u64(seed), &room_config);
+
+            assert!(room.tiles.contains(&DungeonTile::Chasm));
+            assert_connected(&room);
+        }
+    }
+
+    #[test]
+    fn leaves_tiny_rooms_unchanged() {
+        let mut room = parse_room("E.E\n...").unwrap();
+        room.pathing();
+
+        add_hazards(
+            &mut Pcg64::seed_from_u64(1),
+            &mut room,
+            &HazardConfig::default(),
+        );
+
+        assert!(!room.tiles.iter().any(|t| t.is_hazard()));
+    }
+
+    fn assert_connected(room: &DungeonRoom) {
+        let protected = protected_tiles(room);
+        let sets = connected_tile_sets(room);
+        assert!(sets
+            .iter()
+            .any(|set| protected.iter().all(|idx| set.contains(idx))));
+    }
+
+    fn open_room() -> DungeonRoom {
+        let mut room = parse_room(
+            "####E####\n\
+             #.......#\n\
+             #.......#\n\
+             E.......E\n\
+             #.......#\n\
+             #.......#\n\
+             ####E####",
+        )
+        .unwrap();
+        room.pathing();
+
+        room
+    }
+}
@@ -108,6 +108,7 @@ impl Default for Position {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dimension {
     pub vertical: usize,
     pub horizontal: usize,
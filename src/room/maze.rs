@@ -0,0 +1,345 @@
+use rand::prelude::*;
+
+use crate::{direction::Direction3D, error::GenerationError, floor::floor_architecture::FloorRoom};
+
+use super::{room::DungeonRoom, room_builder::RoomBuilder, tile::DungeonTile};
+
+/// Builds labyrinth rooms with a recursive backtracker.
+/// Maze cells are placed on odd rows and columns, so the builder works best with odd room dimensions.
+/// With an even number of rows or columns, the last row or column stays a wall, except for the passages to the exits.
+///
+/// A loop percent of 0 creates a perfect maze, where every two tiles are connected by exactly one path.
+/// Higher values knock out the according share of the remaining inner walls between cells, which creates loops.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct MazeRoomBuilder {
+    pub rows: usize,
+    pub cols: usize,
+    pub loop_percent: u8,
+}
+
+impl Default for MazeRoomBuilder {
+    fn default() -> Self {
+        Self {
+            rows: 15,
+            cols: 15,
+            loop_percent: 0,
+        }
+    }
+}
+
+impl RoomBuilder for MazeRoomBuilder {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create maze room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        if self.rows < 3 || self.cols < 3 {
+            return Err(GenerationError::InvalidConfig(
+                "maze rooms need at least 3 rows and columns".to_string(),
+            ));
+        }
+
+        let mut room = DungeonRoom {
+            tiles: vec![DungeonTile::Wall; self.rows * self.cols],
+            rows: self.rows,
+            columns: self.cols,
+            stair_up: room_config.stair_up,
+            stair_down: room_config.stair_down,
+            ..Default::default()
+        };
+
+        self.carve(rng, &mut room);
+        self.add_loops(rng, &mut room);
+        self.set_exits(&mut room, &room_config.exits);
+        room.pathing();
+
+        Ok(room)
+    }
+
+    fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    fn get_cols(&self) -> usize {
+        self.cols
+    }
+}
+
+impl MazeRoomBuilder {
+    fn cell_rows(&self) -> usize {
+        (self.rows - 1) / 2
+    }
+
+    fn cell_cols(&self) -> usize {
+        (self.cols - 1) / 2
+    }
+
+    // Carves a perfect maze, starting from the top left cell and backtracking whenever a cell has no unvisited neighbors
+    fn carve(&self, rng: &mut dyn RngCore, room: &mut DungeonRoom) {
+        let cell_rows = self.cell_rows();
+        let cell_cols = self.cell_cols();
+        if cell_rows == 0 || cell_cols == 0 {
+            return;
+        }
+
+        let mut visited = vec![false; cell_rows * cell_cols];
+        let mut stack = vec![(0, 0)];
+        visited[0] = true;
+        self.carve_cell(room, 0, 0);
+
+        while let Some(&(row, col)) = stack.last() {
+            let mut neighbors = vec![];
+            if row > 0 && !visited[(row - 1) * cell_cols + col] {
+                neighbors.push((row - 1, col));
+            }
+            if row < cell_rows - 1 && !visited[(row + 1) * cell_cols + col] {
+                neighbors.push((row + 1, col));
+            }
+            if col > 0 && !visited[row * cell_cols + col - 1] {
+                neighbors.push((row, col - 1));
+            }
+            if col < cell_cols - 1 && !visited[row * cell_cols + col + 1] {
+                neighbors.push((row, col + 1));
+            }
+
+            match neighbors.choose(rng) {
+                None => {
+                    stack.pop();
+                }
+                Some(&(next_row, next_col)) => {
+                    visited[next_row * cell_cols + next_col] = true;
+                    self.carve_cell(room, next_row, next_col);
+                    // the wall between two cells sits right in the middle of their tile positions
+                    let idx = room.room_idx(row + next_row + 1, col + next_col + 1);
+                    room.tiles[idx] = DungeonTile::Floor;
+                    stack.push((next_row, next_col));
+                }
+            }
+        }
+    }
+
+    fn carve_cell(&self, room: &mut DungeonRoom, cell_row: usize, cell_col: usize) {
+        let idx = room.room_idx(cell_row * 2 + 1, cell_col * 2 + 1);
+        room.tiles[idx] = DungeonTile::Floor;
+    }
+
+    // Removes inner walls separating two neighboring cells, according to the loop percentage
+    fn add_loops(&self, rng: &mut dyn RngCore, room: &mut DungeonRoom) {
+        if self.loop_percent == 0 {
+            return;
+        }
+
+        let last_row = self.cell_rows() * 2;
+        let last_col = self.cell_cols() * 2;
+        for row in 1..last_row {
+            for col in 1..last_col {
+                // walls between cells have exactly one even coordinate
+                if (row % 2 == 0) == (col % 2 == 0) {
+                    continue;
+                }
+
+                let idx = room.room_idx(row, col);
+                if room.tiles[idx] == DungeonTile::Wall && rng.gen_range(0..100) < self.loop_percent
+                {
+                    room.tiles[idx] = DungeonTile::Floor;
+                }
+            }
+        }
+    }
+
+    // Opens the border next to the center cell of every requested side
+    fn set_exits(&self, room: &mut DungeonRoom, exits: &[Direction3D]) {
+        if self.cell_rows() == 0 || self.cell_cols() == 0 {
+            return;
+        }
+
+        // exits need to lead into a cell, which always sits on an odd row and column
+        let center_row = ((self.rows / 2) | 1).min(self.cell_rows() * 2 - 1);
+        let center_col = ((self.cols / 2) | 1).min(self.cell_cols() * 2 - 1);
+        // with an even size, a wall row or column lies between the last cells and the border
+        let last_cell_row = self.cell_rows() * 2 - 1;
+        let last_cell_col = self.cell_cols() * 2 - 1;
+        for direction in exits {
+            let passage: Vec<usize> = match direction {
+                Direction3D::Top => vec![room.room_idx(0, center_col)],
+                Direction3D::Bottom => (last_cell_row + 1..self.rows)
+                    .map(|row| room.room_idx(row, center_col))
+                    .collect(),
+                Direction3D::Left => vec![room.room_idx(center_row, 0)],
+                Direction3D::Right => (last_cell_col + 1..self.cols)
+                    .map(|col| room.room_idx(center_row, col))
+                    .collect(),
+                _ => continue,
+            };
+
+            if let Some((exit, floors)) = passage.split_last() {
+                for idx in floors {
+                    room.tiles[*idx] = DungeonTile::Floor;
+                }
+                room.tiles[*exit] = DungeonTile::Exit;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::room::{
+        pathfinding::{connected_tile_sets, find_path, walking_cost, Movement},
+        print::print_room,
+    };
+
+    use rand_pcg::Pcg64;
+
+    use super::*;
+
+    #[test]
+    fn creates_printable_room() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = MazeRoomBuilder {
+            rows: 11,
+            cols: 11,
+            ..Default::default()
+        };
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top, Direction3D::Left],
+            ..Default::default()
+        };
+
+        let room = sut.create_room(&mut rng, &room_config);
+
+        let expected_tiles = "#####E#####
+#.#.....#.#
+#.###.#.#.#
+#.....#.#.#
+#######.#.#
+E.....#.#.#
+#.###.#.#.#
+#...#...#.#
+###.#####.#
+#.........#
+###########"
+            .to_string();
+        let room_tile_str = print_room(room.rows, room.columns, room.tiles, 0, 0);
+        assert_eq!(expected_tiles, room_tile_str);
+    }
+
+    #[test]
+    fn creates_perfect_maze_without_loop_percent() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = MazeRoomBuilder::default();
+
+        let room = sut.create_room(&mut rng, &FloorRoom::default());
+
+        // a spanning tree over all cells has one passage less than it has cells
+        let cells = sut.cell_rows() * sut.cell_cols();
+        let floors = room
+            .tiles
+            .iter()
+            .filter(|t| **t == DungeonTile::Floor)
+            .count();
+        assert_eq!(cells * 2 - 1, floors);
+        assert_eq!(1, connected_tile_sets(&room).len());
+    }
+
+    #[test]
+    fn creates_loops_with_loop_percent() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = MazeRoomBuilder {
+            loop_percent: 50,
+            ..Default::default()
+        };
+
+        let room = sut.create_room(&mut rng, &FloorRoom::default());
+
+        let cells = sut.cell_rows() * sut.cell_cols();
+        let floors = room
+            .tiles
+            .iter()
+            .filter(|t| **t == DungeonTile::Floor)
+            .count();
+        assert!(floors > cells * 2 - 1);
+        assert_eq!(1, connected_tile_sets(&room).len());
+    }
+
+    #[test]
+    fn connects_exits_of_even_sized_rooms() {
+        let room_config = FloorRoom {
+            exits: vec![
+                Direction3D::Top,
+                Direction3D::Bottom,
+                Direction3D::Left,
+                Direction3D::Right,
+            ],
+            ..Default::default()
+        };
+
+        for (rows, cols) in [(12, 12), (10, 15), (15, 10), (4, 4)] {
+            let sut = MazeRoomBuilder {
+                rows,
+                cols,
+                loop_percent: 0,
+            };
+            let room = sut.create_room(&mut Pcg64::seed_from_u64(1), &room_config);
+
+            let start = room.room_idx(1, 1);
+            let exits: Vec<usize> = (0..room.tiles.len())
+                .filter(|idx| room.tiles[*idx] == DungeonTile::Exit)
+                .collect();
+            assert_eq!(4, exits.len());
+            for exit in exits {
+                assert!(find_path(&room, exit, start, Movement::FourWay, walking_cost).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_too_small_rooms() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = MazeRoomBuilder {
+            rows: 0,
+            ..Default::default()
+        };
+
+        let result = sut.try_create_room(&mut rng, &FloorRoom::default());
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn opens_exactly_the_requested_sides() {
+        let sut = MazeRoomBuilder {
+            rows: 13,
+            cols: 17,
+            loop_percent: 20,
+        };
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Bottom, Direction3D::Right],
+            ..Default::default()
+        };
+
+        for seed in 0..10 {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let room = sut.create_room(&mut rng, &room_config);
+
+            assert_eq!(2, room.exits.len());
+            assert_eq!(2, room.exit_directions.len());
+            assert!(room.exit_directions.contains(&Direction3D::Bottom));
+            assert!(room.exit_directions.contains(&Direction3D::Right));
+            for exit in room.exits.iter() {
+                assert_eq!(DungeonTile::Exit, room.tiles[*exit]);
+                assert!(room.pathing.contains(exit));
+            }
+        }
+    }
+}
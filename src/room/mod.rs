@@ -1,10 +1,19 @@
+pub mod attributes;
 pub mod automata;
+pub mod bsp;
+pub mod distance;
+pub mod door;
 pub mod drunkard;
 pub mod grid;
+pub mod hazard;
 pub mod math;
+pub mod maze;
 pub mod pathfinding;
+pub mod prefab;
 pub mod print;
 pub mod rectangles;
 pub mod room;
 pub mod room_builder;
 pub mod tile;
+pub mod transform;
+pub mod wfc;
@@ -1,85 +1,213 @@
 use super::room::DungeonRoom;
-use super::tile::DungeonTile;
-use std::collections::HashSet;
-use std::iter::FromIterator;
+use super::tile::{DungeonTile, TileGrid};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+// step costs are scaled, so diagonal steps can cost roughly the square root of 2 times a straight step
+const STRAIGHT_STEP: u32 = 10;
+const DIAGONAL_STEP: u32 = 14;
+
+/// Connected areas of walkable tiles within a room.
+/// Every walkable tile is labelled with the region it belongs to; walls have no label.
+/// Regions are numbered in the order of their lowest tile index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileRegions {
+    pub labels: Vec<Option<usize>>,
+    pub sizes: Vec<usize>,
+}
 
-pub fn connected_tile_sets(room: &DungeonRoom) -> Vec<HashSet<usize>> {
-    let mut connected_tile_sets: Vec<HashSet<usize>> = vec![];
+impl TileRegions {
+    /// Labels the regions of a room with a flood fill, visiting every tile once
+    pub fn label(room: &DungeonRoom) -> Self {
+        let mut labels = vec![None; room.tiles.len()];
+        let mut sizes = vec![];
+        let mut stack = vec![];
 
-    for (idx, t) in room.tiles.iter().enumerate() {
-        if *t == DungeonTile::Wall {
-            continue;
-        }
+        for (idx, t) in room.tiles.iter().enumerate() {
+            if !t.is_walkable() || labels[idx].is_some() {
+                continue;
+            }
 
-        let mut found_existing_set = false;
-        for neigh in neighbor_floors(room, idx) {
-            for t_idx in 0..connected_tile_sets.len() {
-                if connected_tile_sets[t_idx].contains(&neigh) {
-                    connected_tile_sets[t_idx] =
-                        HashSet::from_iter(connected_tile_sets[t_idx].clone());
-                    connected_tile_sets[t_idx].insert(idx);
-                    found_existing_set = true;
+            let region = sizes.len();
+            let mut size = 0;
+            labels[idx] = Some(region);
+            stack.push(idx);
+            while let Some(current) = stack.pop() {
+                size += 1;
+                for neigh in neighbor_floors(room, current) {
+                    if labels[neigh].is_none() {
+                        labels[neigh] = Some(region);
+                        stack.push(neigh);
+                    }
                 }
             }
+            sizes.push(size);
         }
 
-        if !found_existing_set {
-            let mut new_set: HashSet<usize> = HashSet::new();
-            new_set.insert(idx);
-            connected_tile_sets.push(new_set);
-        }
+        Self { labels, sizes }
     }
 
-    merge(connected_tile_sets)
-}
+    /// The region with the most tiles; on a tie, the region numbered last wins
+    pub fn largest(&self) -> Option<usize> {
+        self.sizes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, size)| **size)
+            .map(|(region, _)| region)
+    }
 
-fn merge(tile_sets: Vec<HashSet<usize>>) -> Vec<HashSet<usize>> {
-    let mut result = tile_sets.clone();
+    /// All tile indexes of a region in ascending order
+    pub fn tiles(&self, region: usize) -> Vec<usize> {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| **label == Some(region))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
 
-    let mut merge_again = true;
-    while merge_again {
-        let mut new_result = vec![];
-        for set in result.iter().filter(|t| t.len() > 0) {
-            new_result.push(set.clone());
+    /// The regions as sets of tile indexes
+    pub fn to_sets(&self) -> Vec<HashSet<usize>> {
+        let mut sets = vec![HashSet::new(); self.sizes.len()];
+        for (idx, label) in self.labels.iter().enumerate() {
+            if let Some(region) = label {
+                sets[*region].insert(idx);
+            }
         }
-        result = new_result.clone();
-        result = eliminate_empty(&result);
-        let (r, m) = merge_iteration(&result);
-        result = r;
-        merge_again = m;
+
+        sets
     }
+}
 
-    result
+/// Sets of walkable tiles connected with each other, ordered by their lowest tile index
+pub fn connected_tile_sets(room: &DungeonRoom) -> Vec<HashSet<usize>> {
+    TileRegions::label(room).to_sets()
 }
 
-fn eliminate_empty(tile_sets: &Vec<HashSet<usize>>) -> Vec<HashSet<usize>> {
-    tile_sets
-        .iter()
-        .filter(|ts| ts.len() > 0)
-        .map(|ts| ts.clone())
-        .collect()
+/// Directions a path may take from one tile to the next
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Movement {
+    /// Only horizontal and vertical steps
+    FourWay,
+    /// Diagonal steps are allowed as well, as long as they do not cut a corner of an impassable tile
+    EightWay,
 }
 
-fn merge_iteration(tile_sets: &Vec<HashSet<usize>>) -> (Vec<HashSet<usize>>, bool) {
-    let mut result = tile_sets.clone();
-    let mut merged = false;
-
-    for idx1 in 0..tile_sets.len() {
-        for idx2 in idx1 + 1..tile_sets.len() {
-            let ts1 = result[idx1].clone();
-            let ts2 = result[idx2].clone();
-            if have_common_elements(&ts1, &ts2) {
-                merged = true;
-            } else {
-                continue;
+/// Default cost function: every walkable tile, including doors, can be walked on with the same cost
+pub fn walking_cost(tile: &DungeonTile) -> Option<u32> {
+    if tile.is_walkable() {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Finds the cheapest path between two tiles of a room with A*.
+/// The cost function returns the cost of entering a tile, or None if the tile can not be entered.
+/// Diagonal steps cost 1.4 times the cost of the entered tile.
+///
+/// The resulting path starts with the from tile and ends with the to tile.
+/// Returns None if there is no path, or if one of the tiles is out of range.
+pub fn find_path<G, C>(
+    grid: &G,
+    from: usize,
+    to: usize,
+    movement: Movement,
+    cost: C,
+) -> Option<Vec<usize>>
+where
+    G: TileGrid,
+    C: Fn(&DungeonTile) -> Option<u32>,
+{
+    let tiles = grid.grid_tiles();
+    if from >= tiles.len() || to >= tiles.len() {
+        return None;
+    }
+
+    // the heuristic must never overestimate, so it assumes the cheapest tile everywhere
+    let min_cost = tiles.iter().filter_map(&cost).min().unwrap_or(0);
+    let cols = grid.grid_columns();
+    let heuristic = |idx: usize| {
+        let rows = (idx / cols).abs_diff(to / cols) as u32;
+        let cols = (idx % cols).abs_diff(to % cols) as u32;
+        let steps = match movement {
+            Movement::FourWay => (rows + cols) * STRAIGHT_STEP,
+            Movement::EightWay => {
+                rows.min(cols) * DIAGONAL_STEP + rows.abs_diff(cols) * STRAIGHT_STEP
             }
+        };
+        steps * min_cost
+    };
+
+    let mut costs = vec![u32::MAX; tiles.len()];
+    let mut came_from = vec![usize::MAX; tiles.len()];
+    let mut open = BinaryHeap::new();
+    costs[from] = 0;
+    open.push(Reverse((heuristic(from), from)));
+
+    while let Some(Reverse((_, current))) = open.pop() {
+        if current == to {
+            let mut path = vec![to];
+            while *path.last().unwrap() != from {
+                path.push(came_from[*path.last().unwrap()]);
+            }
+            path.reverse();
+            return Some(path);
+        }
 
-            result[idx1] = union_hashsets(&ts1, &ts2);
-            result[idx2] = HashSet::new();
+        for (next, step) in neighbor_steps(grid, current, movement, &cost) {
+            let tile_cost = match cost(&tiles[next]) {
+                Some(c) => c,
+                None => continue,
+            };
+            let next_cost = costs[current].saturating_add(step * tile_cost);
+            if next_cost < costs[next] {
+                costs[next] = next_cost;
+                came_from[next] = current;
+                open.push(Reverse((next_cost.saturating_add(heuristic(next)), next)));
+            }
         }
     }
 
-    (result, merged)
+    None
+}
+
+// Neighboring tiles, which can be reached from idx with a single step, and the scaled cost of that step
+pub(crate) fn neighbor_steps<G, C>(
+    grid: &G,
+    idx: usize,
+    movement: Movement,
+    cost: &C,
+) -> Vec<(usize, u32)>
+where
+    G: TileGrid,
+    C: Fn(&DungeonTile) -> Option<u32>,
+{
+    let tiles = grid.grid_tiles();
+    let rows = grid.grid_rows() as i32;
+    let cols = grid.grid_columns() as i32;
+    let row = (idx as i32) / cols;
+    let col = (idx as i32) % cols;
+    let passable = |r: i32, c: i32| {
+        r >= 0 && c >= 0 && r < rows && c < cols && cost(&tiles[(r * cols + c) as usize]).is_some()
+    };
+
+    let mut result = vec![];
+    for (dr, dc) in [(-1, 0), (0, -1), (0, 1), (1, 0)] {
+        if passable(row + dr, col + dc) {
+            result.push((((row + dr) * cols + col + dc) as usize, STRAIGHT_STEP));
+        }
+    }
+
+    if movement == Movement::EightWay {
+        for (dr, dc) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+            if passable(row + dr, col + dc) && passable(row + dr, col) && passable(row, col + dc) {
+                result.push((((row + dr) * cols + col + dc) as usize, DIAGONAL_STEP));
+            }
+        }
+    }
+
+    result
 }
 
 fn neighbor_floors(room: &DungeonRoom, idx: usize) -> Vec<usize> {
@@ -88,36 +216,29 @@ fn neighbor_floors(room: &DungeonRoom, idx: usize) -> Vec<usize> {
 
     let mut result = vec![];
 
-    if row > 0 && room.tiles[idx - (room.columns as usize)] != DungeonTile::Wall {
+    if row > 0 && room.tiles[idx - (room.columns as usize)].is_walkable() {
         result.push(idx - room.columns as usize);
     }
 
-    if col > 0 && room.tiles[idx - 1] != DungeonTile::Wall {
+    if col > 0 && room.tiles[idx - 1].is_walkable() {
         result.push(idx - 1);
     }
 
-    if col < room.columns - 1 && room.tiles[idx + 1] != DungeonTile::Wall {
+    if col < room.columns - 1 && room.tiles[idx + 1].is_walkable() {
         result.push(idx + 1);
     }
 
-    if row < room.rows - 1 && room.tiles[idx + (room.columns as usize)] != DungeonTile::Wall {
+    if row < room.rows - 1 && room.tiles[idx + (room.columns as usize)].is_walkable() {
         result.push(idx + room.columns as usize);
     }
 
     result
 }
 
-fn union_hashsets(set1: &HashSet<usize>, set2: &HashSet<usize>) -> HashSet<usize> {
-    HashSet::from_iter(set1.union(set2).map(|e| *e))
-}
-
-fn have_common_elements(set1: &HashSet<usize>, set2: &HashSet<usize>) -> bool {
-    set1.intersection(set2).count() > 0
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{dungeon::room::ArrangedDungeonRoom, room::print::parse_room};
 
     #[test]
     fn connected_tile_sets_returns_list_of_connected_areas() {
@@ -142,63 +263,157 @@ mod test {
     }
 
     #[test]
-    fn sorts_out_empty_hashsets() {
-        let vec = vec![HashSet::new(), HashSet::from_iter(0..3)];
-        let result = eliminate_empty(&vec);
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].len(), 3);
+    fn labels_connected_regions_in_order_of_their_first_tile() {
+        // .#..
+        // ##..
+        // ..#.
+        // ..#.
+        let mut tiles = vec![DungeonTile::Floor; 16];
+        for idx in [1, 4, 5, 10, 14] {
+            tiles[idx] = DungeonTile::Wall;
+        }
+        let room = DungeonRoom {
+            tiles,
+            rows: 4,
+            columns: 4,
+            ..Default::default()
+        };
+
+        let result = TileRegions::label(&room);
+
+        assert_eq!(vec![1, 6, 4], result.sizes);
+        assert_eq!(Some(0), result.labels[0]);
+        assert_eq!(None, result.labels[1]);
+        assert_eq!(Some(1), result.labels[15]);
+        assert_eq!(Some(2), result.labels[8]);
+        assert_eq!(Some(1), result.largest());
+        assert_eq!(vec![8, 9, 12, 13], result.tiles(2));
+    }
+
+    #[test]
+    fn largest_region_prefers_last_region_on_tie() {
+        let room = DungeonRoom {
+            tiles: vec![DungeonTile::Floor, DungeonTile::Wall, DungeonTile::Floor],
+            rows: 1,
+            columns: 3,
+            ..Default::default()
+        };
+
+        let result = TileRegions::label(&room);
+
+        assert_eq!(Some(1), result.largest());
     }
 
     #[test]
-    fn merge_iteration_returns_true_if_a_merge_occured() {
-        let set1: HashSet<usize> = HashSet::from_iter(0..3);
-        let set2: HashSet<usize> = HashSet::from_iter(2..5);
-        let result = merge_iteration(&vec![set1, set2]);
-
-        assert!(result.1);
-        assert_eq!(result.0[0].len(), 5);
-        assert_eq!(result.0[1].len(), 0);
+    fn labels_every_walkable_tile_of_large_room() {
+        let mut tiles = vec![DungeonTile::Floor; 512 * 512];
+        for idx in (0..tiles.len()).step_by(3) {
+            tiles[idx] = DungeonTile::Wall;
+        }
+        let room = DungeonRoom {
+            tiles,
+            rows: 512,
+            columns: 512,
+            ..Default::default()
+        };
+
+        let result = TileRegions::label(&room);
+
+        assert_eq!(
+            512 * 512 - (512 * 512_usize).div_ceil(3),
+            result.sizes.iter().sum::<usize>()
+        );
     }
 
     #[test]
-    fn merge_iteration_returns_false_if_no_merge_occured() {
-        let set1: HashSet<usize> = HashSet::from_iter(0..3);
-        let set2: HashSet<usize> = HashSet::from_iter(4..6);
-        let result = merge_iteration(&vec![set1, set2]);
-
-        assert!(!result.1);
-        assert_eq!(result.0[0].len(), 3);
-        assert_eq!(result.0[1].len(), 2);
+    fn finds_shortest_path_around_walls() {
+        // ....
+        // ###.
+        // ....
+        let room = create_room("....\n###.\n....");
+
+        let result = find_path(&room, 0, 8, Movement::FourWay, walking_cost);
+
+        assert_eq!(Some(vec![0, 1, 2, 3, 7, 11, 10, 9, 8]), result);
     }
 
     #[test]
-    fn union_hashset_test() {
-        let mut set1: HashSet<usize> = HashSet::new();
-        set1.insert(1);
-        set1.insert(2);
-        set1.insert(3);
-        let mut set2: HashSet<usize> = HashSet::new();
-        set2.insert(1);
-        set2.insert(4);
-
-        let result = union_hashsets(&set1, &set2);
-        assert_eq!(result.len(), 4);
+    fn finds_no_path_between_separated_tiles() {
+        let room = create_room("..#..\n..#..");
+
+        let result = find_path(&room, 0, 4, Movement::FourWay, walking_cost);
+
+        assert_eq!(None, result);
     }
 
     #[test]
-    fn returns_true_if_sets_have_same_entries() {
-        let set1 = HashSet::from_iter(0..3);
-        let set2 = HashSet::from_iter(2..4);
-        let result = have_common_elements(&set1, &set2);
-        assert!(result);
+    fn finds_no_path_to_tiles_out_of_range() {
+        let room = create_room("...");
+
+        let result = find_path(&room, 0, 3, Movement::FourWay, walking_cost);
+
+        assert_eq!(None, result);
     }
 
     #[test]
-    fn returns_false_if_sets_do_not_have_same_entries() {
-        let set1 = HashSet::from_iter(0..2);
-        let set2 = HashSet::from_iter(3..5);
-        let result = have_common_elements(&set1, &set2);
-        assert!(!result);
+    fn finds_path_to_itself() {
+        let room = create_room("...");
+
+        let result = find_path(&room, 1, 1, Movement::FourWay, walking_cost);
+
+        assert_eq!(Some(vec![1]), result);
+    }
+
+    #[test]
+    fn takes_diagonal_steps_with_eight_way_movement() {
+        let room = create_room("...\n...\n...");
+
+        let four_way = find_path(&room, 0, 8, Movement::FourWay, walking_cost).unwrap();
+        let eight_way = find_path(&room, 0, 8, Movement::EightWay, walking_cost);
+
+        assert_eq!(5, four_way.len());
+        assert_eq!(Some(vec![0, 4, 8]), eight_way);
+    }
+
+    #[test]
+    fn does_not_cut_corners_with_eight_way_movement() {
+        let room = create_room("..\n#.");
+
+        let result = find_path(&room, 0, 3, Movement::EightWay, walking_cost);
+
+        assert_eq!(Some(vec![0, 1, 3]), result);
+    }
+
+    #[test]
+    fn avoids_expensive_tiles() {
+        // .E.
+        // ...
+        let room = create_room(".E.\n...");
+        let avoid_exits = |t: &DungeonTile| match t {
+            DungeonTile::Wall => None,
+            DungeonTile::Exit => Some(10),
+            _ => Some(1),
+        };
+
+        let direct = find_path(&room, 0, 2, Movement::FourWay, walking_cost);
+        let detour = find_path(&room, 0, 2, Movement::FourWay, avoid_exits);
+
+        assert_eq!(Some(vec![0, 1, 2]), direct);
+        assert_eq!(Some(vec![0, 3, 4, 5, 2]), detour);
+    }
+
+    #[test]
+    fn finds_path_in_arranged_room() {
+        let room = create_room(".#.\n...");
+        let arranged = ArrangedDungeonRoom::from(&room);
+
+        let result = find_path(&arranged, 0, 2, Movement::FourWay, walking_cost);
+
+        assert_eq!(Some(vec![0, 3, 4, 5, 2]), result);
+    }
+
+    fn create_room(tiles: &str) -> DungeonRoom {
+        parse_room(tiles).unwrap()
     }
 
     #[test]
@@ -272,19 +487,4 @@ mod test {
         let expected: Vec<usize> = vec![1, 3];
         assert_eq!(expected, result);
     }
-
-    #[test]
-    fn merge_tile_sets_with_intersect() {
-        let set1: HashSet<usize> = HashSet::from_iter(0..3);
-        let set2: HashSet<usize> = HashSet::from_iter(2..5);
-        let set3: HashSet<usize> = HashSet::from_iter(6..8);
-        let all = vec![set1, set2, set3];
-
-        let result = merge(all);
-
-        let expected_set1: HashSet<usize> = HashSet::from_iter(0..5);
-        let expected_set2: HashSet<usize> = HashSet::from_iter(6..8);
-        let expected_all: Vec<HashSet<usize>> = vec![expected_set1, expected_set2];
-        assert_eq!(expected_all, result);
-    }
 }
@@ -0,0 +1,418 @@
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use rand::prelude::*;
+
+use crate::{direction::Direction3D, error::GenerationError, floor::floor_architecture::FloorRoom};
+
+use super::{
+    print::parse_room,
+    room::DungeonRoom,
+    room_builder::RoomBuilder,
+    tile::DungeonTile,
+    transform::{mirror_tiles, rotate_tiles},
+};
+
+/// A hand-authored room.
+///
+/// Prefab files start with a header declaring the sides of the room which can take exits,
+/// followed by the room tiles in the format of print_room:
+///
+/// ```text
+/// name: shrine
+/// exits: Top Left
+/// ###E###
+/// #.....#
+/// E..^..#
+/// #######
+/// ```
+///
+/// Every declared side needs at least one exit tile on its border, and exit tiles are only allowed on declared sides.
+/// The name is optional and defaults to the file name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prefab {
+    pub name: String,
+    pub rows: usize,
+    pub columns: usize,
+    pub tiles: Vec<DungeonTile>,
+    pub exits: Vec<Direction3D>,
+}
+
+impl Prefab {
+    /// Parses a prefab; returns None if the header or the room tiles are invalid
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut name = String::new();
+        let mut exits = None;
+        let mut room_lines = vec![];
+        for line in input.lines() {
+            if let Some(value) = line.strip_prefix("name:") {
+                name = value.trim().to_string();
+            } else if let Some(value) = line.strip_prefix("exits:") {
+                exits = Some(parse_directions(value)?);
+            } else {
+                room_lines.push(line);
+            }
+        }
+
+        let room = parse_room(&room_lines.join("\n"))?;
+        let prefab = Self {
+            name,
+            rows: room.rows,
+            columns: room.columns,
+            tiles: room.tiles,
+            exits: exits?,
+        };
+
+        if prefab.exit_sides() != prefab.sorted_exits() {
+            return None;
+        }
+
+        Some(prefab)
+    }
+
+    /// Loads a prefab file
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let content = fs::read_to_string(path)?.replace("\r\n", "\n");
+        let mut prefab = Self::parse(&content).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid prefab file {}", path.display()),
+            )
+        })?;
+
+        if prefab.name.is_empty() {
+            if let Some(stem) = path.file_stem() {
+                prefab.name = stem.to_string_lossy().to_string();
+            }
+        }
+
+        Ok(prefab)
+    }
+
+    /// The prefab turned by 90 degrees clockwise
+    pub fn rotated(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            rows: self.columns,
+            columns: self.rows,
+            tiles: rotate_tiles(self.rows, self.columns, &self.tiles),
+            exits: self.exits.iter().map(|e| e.rotate_clockwise()).collect(),
+        }
+    }
+
+    /// The prefab mirrored along its vertical axis
+    pub fn mirrored(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            rows: self.rows,
+            columns: self.columns,
+            tiles: mirror_tiles(self.columns, &self.tiles),
+            exits: self.exits.iter().map(|e| e.mirror_horizontally()).collect(),
+        }
+    }
+
+    /// All distinct rotations and mirrorings of the prefab, starting with the prefab as it is
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut result: Vec<Self> = vec![];
+        let mut current = self.clone();
+        for _ in 0..4 {
+            for candidate in [current.clone(), current.mirrored()] {
+                if !result.iter().any(|r| r.is_same_shape(&candidate)) {
+                    result.push(candidate);
+                }
+            }
+            current = current.rotated();
+        }
+
+        result
+    }
+
+    fn is_same_shape(&self, other: &Self) -> bool {
+        self.rows == other.rows && self.columns == other.columns && self.tiles == other.tiles
+    }
+
+    fn sorted_exits(&self) -> Vec<Direction3D> {
+        let mut exits = self.exits.clone();
+        exits.sort_by_key(|e| *e as u8);
+        exits.dedup();
+        exits
+    }
+
+    // Sides of the room which have an exit tile on their border
+    fn exit_sides(&self) -> Vec<Direction3D> {
+        let mut sides = vec![];
+        for (idx, tile) in self.tiles.iter().enumerate() {
+            if *tile != DungeonTile::Exit {
+                continue;
+            }
+
+            for side in self.sides_of(idx) {
+                if !sides.contains(&side) {
+                    sides.push(side);
+                }
+            }
+        }
+
+        sides.sort_by_key(|e| *e as u8);
+        sides
+    }
+
+    fn sides_of(&self, idx: usize) -> Vec<Direction3D> {
+        let row = idx / self.columns;
+        let col = idx % self.columns;
+        let mut sides = vec![];
+        if row == 0 {
+            sides.push(Direction3D::Top);
+        }
+        if row == self.rows - 1 {
+            sides.push(Direction3D::Bottom);
+        }
+        if col == 0 {
+            sides.push(Direction3D::Left);
+        }
+        if col == self.columns - 1 {
+            sides.push(Direction3D::Right);
+        }
+
+        sides
+    }
+}
+
+fn parse_directions(value: &str) -> Option<Vec<Direction3D>> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| match s {
+            "Top" => Some(Direction3D::Top),
+            "Bottom" => Some(Direction3D::Bottom),
+            "Left" => Some(Direction3D::Left),
+            "Right" => Some(Direction3D::Right),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Builds rooms from hand-authored prefabs.
+/// A prefab fits a room, if it can take exits on every side the room needs exits on; prefabs are rotated and mirrored to fit.
+/// Among the fitting prefabs, one with the least superfluous exit sides is chosen randomly, and its superfluous exits are walled up.
+///
+/// Hand-authored rooms can not fit every room of a floor layout, so rooms no prefab fits are built by the fallback builder.
+pub struct PrefabRoomBuilder {
+    pub prefabs: Vec<Prefab>,
+    pub fallback: Box<dyn RoomBuilder>,
+}
+
+impl PrefabRoomBuilder {
+    pub fn new(prefabs: Vec<Prefab>, fallback: Box<dyn RoomBuilder>) -> Self {
+        Self { prefabs, fallback }
+    }
+
+    /// Loads all prefab files with a "txt" extension from a directory, ordered by file name
+    pub fn load_dir(path: &Path, fallback: Box<dyn RoomBuilder>) -> std::io::Result<Self> {
+        let mut files = vec![];
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.extension().map(|e| e == "txt").unwrap_or(false) {
+                files.push(file);
+            }
+        }
+        files.sort();
+
+        let prefabs = files
+            .iter()
+            .map(|f| Prefab::load(f))
+            .collect::<std::io::Result<Vec<Prefab>>>()?;
+
+        Ok(Self::new(prefabs, fallback))
+    }
+
+    fn fitting_prefabs(&self, exits: &[Direction3D]) -> Vec<Prefab> {
+        let candidates: Vec<Prefab> = self
+            .prefabs
+            .iter()
+            .flat_map(|p| p.orientations())
+            .filter(|p| exits.iter().all(|e| p.exits.contains(e)))
+            .collect();
+
+        let least_superfluous = candidates.iter().map(|p| p.exits.len()).min();
+        candidates
+            .into_iter()
+            .filter(|p| Some(p.exits.len()) == least_superfluous)
+            .collect()
+    }
+}
+
+impl RoomBuilder for PrefabRoomBuilder {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create prefab room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        let prefab = match self.fitting_prefabs(&room_config.exits).choose(rng) {
+            Some(prefab) => prefab.clone(),
+            None => return self.fallback.try_create_room(rng, room_config),
+        };
+
+        let mut room = DungeonRoom {
+            tiles: prefab.tiles,
+            rows: prefab.rows,
+            columns: prefab.columns,
+            stair_up: room_config.stair_up,
+            stair_down: room_config.stair_down,
+            ..Default::default()
+        };
+
+        // stairs are placed by the dungeon builder, according to the floor layout
+        for tile in room.tiles.iter_mut() {
+            if *tile == DungeonTile::StairsUp || *tile == DungeonTile::StairsDown {
+                *tile = DungeonTile::Floor;
+            }
+        }
+
+        room.pathing();
+        for superfluous in prefab
+            .exits
+            .iter()
+            .filter(|e| !room_config.exits.contains(e))
+        {
+            room.close_side(*superfluous);
+        }
+
+        Ok(room)
+    }
+
+    fn get_rows(&self) -> usize {
+        self.prefabs.iter().map(|p| p.rows).max().unwrap_or(0)
+    }
+
+    fn get_cols(&self) -> usize {
+        self.prefabs.iter().map(|p| p.columns).max().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::room::{grid::GridRoomBuilder, print::print_room};
+
+    use rand_pcg::Pcg64;
+
+    use super::*;
+
+    const SHRINE: &str = "name: shrine
+exits: Top, Left
+###E###
+#.....#
+E.....#
+#######";
+
+    #[test]
+    fn parses_prefab() {
+        let result = Prefab::parse(SHRINE).unwrap();
+
+        assert_eq!("shrine", result.name);
+        assert_eq!(4, result.rows);
+        assert_eq!(7, result.columns);
+        assert_eq!(vec![Direction3D::Top, Direction3D::Left], result.exits);
+    }
+
+    #[test]
+    fn does_not_parse_prefab_with_undeclared_exits() {
+        let result = Prefab::parse("exits: Top\n#E#\nE.#\n###");
+
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn does_not_parse_prefab_without_exit_declaration() {
+        let result = Prefab::parse("#E#\n#.#\n###");
+
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn rotates_prefab_with_exits() {
+        let prefab = Prefab::parse(SHRINE).unwrap();
+
+        let result = prefab.rotated();
+
+        assert_eq!(vec![Direction3D::Right, Direction3D::Top], result.exits);
+        assert_eq!(
+            "#E##\n#..#\n#..#\n#..E\n#..#\n#..#\n####",
+            print_room(result.rows, result.columns, result.tiles, 0, 0)
+        );
+    }
+
+    #[test]
+    fn lists_distinct_orientations() {
+        let asymmetric = Prefab::parse(SHRINE).unwrap();
+        let symmetric = Prefab::parse("exits: Top Bottom Left Right\n#E#\nE.E\n#E#").unwrap();
+
+        assert_eq!(8, asymmetric.orientations().len());
+        assert_eq!(1, symmetric.orientations().len());
+    }
+
+    #[test]
+    fn loads_prefabs_from_directory() {
+        let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        dir.push("resources/prefabs");
+
+        let result = PrefabRoomBuilder::load_dir(&dir, Box::new(GridRoomBuilder::default()))
+            .expect("unable to load prefabs");
+
+        let names: Vec<&str> = result.prefabs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(vec!["boss", "shrine"], names);
+    }
+
+    #[test]
+    fn builds_room_from_rotated_prefab() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = PrefabRoomBuilder::new(
+            vec![Prefab::parse(SHRINE).unwrap()],
+            Box::new(GridRoomBuilder::default()),
+        );
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Bottom],
+            ..Default::default()
+        };
+
+        let room = sut.create_room(&mut rng, &room_config);
+
+        assert_eq!(vec![Direction3D::Bottom], room.exit_directions);
+        assert_eq!(
+            1,
+            room.tiles
+                .iter()
+                .filter(|t| **t == DungeonTile::Exit)
+                .count()
+        );
+        assert_eq!(28, room.tiles.len());
+    }
+
+    #[test]
+    fn falls_back_if_no_prefab_fits() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = PrefabRoomBuilder::new(
+            vec![Prefab::parse(SHRINE).unwrap()],
+            Box::new(GridRoomBuilder::default()),
+        );
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top, Direction3D::Left, Direction3D::Right],
+            ..Default::default()
+        };
+
+        let room = sut.create_room(&mut rng, &room_config);
+
+        assert_eq!(13, room.rows);
+        assert_eq!(13, room.columns);
+    }
+}
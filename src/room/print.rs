@@ -1,4 +1,4 @@
-use super::tile::DungeonTile;
+use super::{room::DungeonRoom, tile::DungeonTile};
 
 pub fn print_room(
     rows: usize,
@@ -21,21 +21,7 @@ pub fn print_room(
             .iter()
             .skip(r * cols)
             .take(cols)
-            .map(|t| {
-                if *t == DungeonTile::Floor {
-                    '.'
-                } else if *t == DungeonTile::Wall {
-                    '#'
-                } else if *t == DungeonTile::Exit {
-                    'E'
-                } else if *t == DungeonTile::StairsDown {
-                    'v'
-                } else if *t == DungeonTile::StairsUp {
-                    '^'
-                } else {
-                    '?'
-                }
-            })
+            .map(tile_char)
             .collect();
 
         output.push_str(&padding);
@@ -56,10 +42,77 @@ pub fn print_room(
     output
 }
 
+/// Character representation of a tile, as used in printed rooms
+pub fn tile_char(tile: &DungeonTile) -> char {
+    match tile {
+        DungeonTile::Floor => '.',
+        DungeonTile::Wall => '#',
+        DungeonTile::Exit => 'E',
+        DungeonTile::StairsDown => 'v',
+        DungeonTile::StairsUp => '^',
+        DungeonTile::Door => '+',
+        DungeonTile::LockedDoor => 'L',
+        DungeonTile::SecretDoor => 'S',
+        DungeonTile::Key => 'k',
+        DungeonTile::Water => '~',
+        DungeonTile::Lava => '%',
+        DungeonTile::Chasm => ':',
+        DungeonTile::Bridge => '=',
+    }
+}
+
+/// Reverse of tile_char: returns the tile a printed character stands for
+pub fn parse_tile(c: char) -> Option<DungeonTile> {
+    match c {
+        '.' => Some(DungeonTile::Floor),
+        '#' => Some(DungeonTile::Wall),
+        'E' => Some(DungeonTile::Exit),
+        'v' => Some(DungeonTile::StairsDown),
+        '^' => Some(DungeonTile::StairsUp),
+        '+' => Some(DungeonTile::Door),
+        'L' => Some(DungeonTile::LockedDoor),
+        'S' => Some(DungeonTile::SecretDoor),
+        'k' => Some(DungeonTile::Key),
+        '~' => Some(DungeonTile::Water),
+        '%' => Some(DungeonTile::Lava),
+        ':' => Some(DungeonTile::Chasm),
+        '=' => Some(DungeonTile::Bridge),
+        _ => None,
+    }
+}
+
+/// Parses a room printed by print_room without paddings.
+/// Returns None if the input contains unknown characters, or if its lines differ in length.
+pub fn parse_room(input: &str) -> Option<DungeonRoom> {
+    let lines: Vec<&str> = input
+        .lines()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.is_empty())
+        .collect();
+    let columns = lines.first()?.chars().count();
+
+    let mut tiles = vec![];
+    for line in lines.iter() {
+        if line.chars().count() != columns {
+            return None;
+        }
+
+        for c in line.chars() {
+            tiles.push(parse_tile(c)?);
+        }
+    }
+
+    Some(DungeonRoom {
+        tiles,
+        rows: lines.len(),
+        columns,
+        ..Default::default()
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::room::{room::DungeonRoom, tile::DungeonTile};
 
     #[test]
     fn prints_empty_room() {
@@ -115,4 +168,26 @@ mod test {
         println!("{}", result);
         assert_eq!("       \n  ...  \n  ...  \n  ...  \n       ", result);
     }
+
+    #[test]
+    fn parses_printed_room() {
+        let result = parse_room("#E#\n.^v\n###\n").unwrap();
+
+        assert_eq!(3, result.rows);
+        assert_eq!(3, result.columns);
+        assert_eq!(
+            "#E#\n.^v\n###",
+            print_room(result.rows, result.columns, result.tiles, 0, 0)
+        );
+    }
+
+    #[test]
+    fn does_not_parse_unknown_characters() {
+        assert_eq!(None, parse_room("#x#\n...").map(|r| r.tiles));
+    }
+
+    #[test]
+    fn does_not_parse_lines_of_different_length() {
+        assert_eq!(None, parse_room("###\n..").map(|r| r.tiles));
+    }
 }
@@ -1,11 +1,15 @@
-use rand::Rng;
-use rand_pcg::Pcg64;
+use rand::{Rng, RngCore};
 
-use crate::floor::floor_architecture::FloorRoom;
+use crate::{error::GenerationError, floor::floor_architecture::FloorRoom};
 
 use super::{math::URect, room::DungeonRoom, room_builder::RoomBuilder, tile::DungeonTile};
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct RectanglesRoomBuilder {
     pub rows: usize,
     pub cols: usize,
@@ -23,7 +27,17 @@ impl Default for RectanglesRoomBuilder {
 }
 
 impl RoomBuilder for RectanglesRoomBuilder {
-    fn create_room(&self, rng: &mut rand_pcg::Pcg64, room_config: &FloorRoom) -> DungeonRoom {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create rectangles room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        self.validate()?;
         let mut rects = self.create_rects(rng);
 
         let mut room = DungeonRoom {
@@ -47,7 +61,7 @@ impl RoomBuilder for RectanglesRoomBuilder {
         rects.sort_by(|r1, r2| r1.center().cmp(&r2.center()));
         self.fill_and_build_corridors(&mut room, &rects, rng);
 
-        room
+        Ok(room)
     }
 
     fn get_rows(&self) -> usize {
@@ -60,7 +74,34 @@ impl RoomBuilder for RectanglesRoomBuilder {
 }
 
 impl RectanglesRoomBuilder {
-    fn create_rects(&self, rng: &mut Pcg64) -> Vec<URect> {
+    // rectangles of the maximum size need to fit into the room, inside of its outer walls
+    fn validate(&self) -> Result<(), GenerationError> {
+        let too_small = || {
+            GenerationError::InvalidConfig(format!(
+                "rectangles room of {}x{} tiles is too small for granularity {:?}",
+                self.rows, self.cols, self.granularity
+            ))
+        };
+
+        if self.rows < 3 || self.cols < 3 {
+            return Err(too_small());
+        }
+
+        if self.granularity == Granularity::Full {
+            return Ok(());
+        }
+
+        let (min, max, _) = self
+            .granularity
+            .size_and_number_ranges(self.rows, self.cols);
+        if min >= max || max + 1 >= self.rows || max + 1 >= self.cols {
+            return Err(too_small());
+        }
+
+        Ok(())
+    }
+
+    fn create_rects(&self, rng: &mut dyn RngCore) -> Vec<URect> {
         if self.granularity == Granularity::Full {
             return vec![URect::new(1, self.rows - 2, 1, self.cols - 2)];
         }
@@ -88,7 +129,7 @@ impl RectanglesRoomBuilder {
         &self,
         min: usize,
         max: usize,
-        rng: &mut Pcg64,
+        rng: &mut dyn RngCore,
         existing_rects: &Vec<URect>,
     ) -> Option<URect> {
         let rect = self.create_rect(min, max, rng);
@@ -102,7 +143,7 @@ impl RectanglesRoomBuilder {
         Some(rect)
     }
 
-    fn create_rect(&self, min: usize, max: usize, rng: &mut Pcg64) -> URect {
+    fn create_rect(&self, min: usize, max: usize, rng: &mut dyn RngCore) -> URect {
         let col_size = rng.gen_range(min..max);
         let row_size = rng.gen_range(min..max);
         let placement_cols = rng.gen_range(1..self.cols - 1 - col_size);
@@ -119,7 +160,7 @@ impl RectanglesRoomBuilder {
         &self,
         room: &mut DungeonRoom,
         rects: &Vec<URect>,
-        rng: &mut Pcg64,
+        rng: &mut dyn RngCore,
     ) {
         let mut ordered_rects = rects.clone();
         ordered_rects.sort_by(|a, b| a.center().cmp(&b.center()));
@@ -173,6 +214,7 @@ impl RectanglesRoomBuilder {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Granularity {
     Small,
     Medium,
@@ -232,6 +274,20 @@ mod test {
         assert_eq!(expected_tiles, room_tile_str);
     }
 
+    #[test]
+    fn rejects_rooms_too_small_for_granularity() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = RectanglesRoomBuilder {
+            rows: 3,
+            cols: 3,
+            granularity: Granularity::Large,
+        };
+
+        let result = sut.try_create_room(&mut rng, &FloorRoom::default());
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
     #[test]
     fn get_room_sizes_and_amount_by_granularity() {
         let small = Granularity::Small.size_and_number_ranges(16, 24);
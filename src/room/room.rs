@@ -1,10 +1,12 @@
 use std::collections::HashSet;
 
-use super::pathfinding::connected_tile_sets;
-use super::tile::DungeonTile;
+use super::attributes::TileAttributes;
+use super::pathfinding::TileRegions;
+use super::tile::{DungeonTile, TileGrid};
 use crate::direction::Direction3D;
 
 #[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DungeonRoom {
     pub tiles: Vec<DungeonTile>,
     pub exits: Vec<usize>,
@@ -14,6 +16,9 @@ pub struct DungeonRoom {
     pub exit_directions: Vec<Direction3D>,
     pub stair_up: bool,
     pub stair_down: bool,
+    /// Game specific values on the tiles, like biomes or light levels
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: TileAttributes,
 }
 
 impl Default for DungeonRoom {
@@ -27,10 +32,25 @@ impl Default for DungeonRoom {
             exit_directions: vec![],
             stair_up: false,
             stair_down: false,
+            attributes: TileAttributes::default(),
         }
     }
 }
 
+impl TileGrid for DungeonRoom {
+    fn grid_tiles(&self) -> &[DungeonTile] {
+        &self.tiles
+    }
+
+    fn grid_rows(&self) -> usize {
+        self.rows
+    }
+
+    fn grid_columns(&self) -> usize {
+        self.columns
+    }
+}
+
 impl DungeonRoom {
     pub fn room_idx(&self, row: usize, col: usize) -> usize {
         ((row * self.columns) + col) as usize
@@ -65,7 +85,7 @@ impl DungeonRoom {
 
     pub fn side_indexes(&self, direction: &Direction3D) -> Vec<usize> {
         match direction {
-            Direction3D::Top => (0..(self.rows as usize)).collect(),
+            Direction3D::Top => (0..(self.columns as usize)).collect(),
             Direction3D::Bottom => {
                 (self.tiles.len() - (self.columns as usize)..self.tiles.len()).collect()
             }
@@ -95,16 +115,16 @@ impl DungeonRoom {
         }
     }
 
+    /// Sets the largest connected region of walkable tiles as pathing, and its border tiles as exits.
+    /// Rooms without walkable tiles get no pathing at all.
+    /// Exits found by an earlier call are replaced, so pathing can be recalculated after changing tiles.
     pub fn pathing(&mut self) {
-        let connected_tiles = connected_tile_sets(self);
-        self.pathing = connected_tiles
-            .iter()
-            .max_by(|t1, t2| t1.len().cmp(&t2.len()))
-            .unwrap()
-            .iter()
-            .map(|t| *t)
-            .collect::<Vec<usize>>();
-        self.pathing.sort();
+        self.exits.clear();
+        let regions = TileRegions::label(self);
+        self.pathing = match regions.largest() {
+            Some(region) => regions.tiles(region),
+            None => vec![],
+        };
 
         for idx in self.pathing.clone() {
             let row = self.row(idx.clone());
@@ -142,7 +162,10 @@ impl DungeonRoom {
             }
         }
 
-        result.into_iter().collect()
+        // sorted, since the iteration order of hash sets differs between runs
+        let mut directions: Vec<Direction3D> = result.into_iter().collect();
+        directions.sort_by_key(|d| *d as u8);
+        directions
     }
 }
 
@@ -168,6 +191,20 @@ mod test {
         );
     }
 
+    #[test]
+    fn side_indexes_for_tiles_of_top_direction_in_wide_room() {
+        let sut = DungeonRoom {
+            tiles: vec![DungeonTile::Floor; 12],
+            columns: 4,
+            rows: 3,
+            ..Default::default()
+        };
+
+        let result = sut.side_indexes(&Direction3D::Top);
+
+        assert_eq!(vec![0, 1, 2, 3], result);
+    }
+
     #[test]
     fn side_indexes_for_tiles_of_bottom_direction() {
         let sut = DungeonRoom {
@@ -270,6 +307,38 @@ mod test {
         assert!(!sut.pathing.contains(&11));
     }
 
+    #[test]
+    fn recalculates_pathing_without_duplicate_exits() {
+        let mut sut = DungeonRoom {
+            tiles: vec![DungeonTile::Floor; 9],
+            columns: 3,
+            rows: 3,
+            ..Default::default()
+        };
+        sut.pathing();
+        let exits = sut.exits.clone();
+
+        sut.pathing();
+
+        assert_eq!(8, exits.len());
+        assert_eq!(exits, sut.exits);
+    }
+
+    #[test]
+    fn calculates_no_pathing_for_rooms_without_floor() {
+        let mut sut = DungeonRoom {
+            tiles: vec![DungeonTile::Wall; 4],
+            columns: 2,
+            rows: 2,
+            ..Default::default()
+        };
+
+        sut.pathing();
+
+        assert!(sut.pathing.is_empty());
+        assert!(sut.exits.is_empty());
+    }
+
     #[test]
     fn calculates_index_from_row_and_col() {
         let sut = build_sut();
@@ -1,13 +1,28 @@
-use rand_pcg::Pcg64;
+use rand::RngCore;
 
 use crate::{
     direction::Direction3D,
+    error::GenerationError,
     floor::floor_architecture::FloorRoom,
     room::{room::DungeonRoom, tile::DungeonTile},
 };
 
+/// Builder trait for creating rooms.
+/// Any random number generator can be used; the same generator state always creates the same room.
+///
+/// create_room panics if the builder is unable to create a room for its parameters;
+/// try_create_room returns an error instead, so invalid configs can be rejected.
 pub trait RoomBuilder {
-    fn create_room(&self, rng: &mut Pcg64, room_config: &FloorRoom) -> DungeonRoom;
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom;
+
+    /// Fallible variant of create_room; builders which can fail override it
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        Ok(self.create_room(rng, room_config))
+    }
 
     fn get_rows(&self) -> usize;
 
@@ -19,7 +34,7 @@ pub trait RoomBuilder {
             .tiles
             .iter()
             .enumerate()
-            .filter(|(_, t)| **t == DungeonTile::Floor)
+            .filter(|(_, t)| **t == DungeonTile::Floor || t.is_door())
             .map(|(idx, _)| (room.row(idx), room.col(idx)))
         {
             if row == self.get_rows() - 1 && !directions.contains(&Direction3D::Top) {
@@ -45,6 +60,8 @@ pub trait RoomBuilder {
 
 #[cfg(test)]
 pub mod test {
+    use rand::RngCore;
+
     use crate::{
         direction::Direction3D,
         floor::floor_architecture::FloorRoom,
@@ -75,6 +92,18 @@ pub mod test {
         assert_eq!(Direction3D::Top, result[1]);
     }
 
+    #[test]
+    pub fn calculates_hit_exits_through_doors() {
+        let builder = DummyRoomBuilder {};
+        let mut room = create_horizontal_hallway();
+        room.tiles[3] = DungeonTile::Door;
+        room.tiles[5] = DungeonTile::SecretDoor;
+
+        let result = builder.get_hit_exits(&room);
+
+        assert_eq!(vec![Direction3D::Left, Direction3D::Right], result);
+    }
+
     fn create_horizontal_hallway() -> DungeonRoom {
         DungeonRoom {
             columns: 3,
@@ -124,7 +153,7 @@ pub mod test {
     impl RoomBuilder for DummyRoomBuilder {
         fn create_room(
             &self,
-            _rng: &mut rand_pcg::Pcg64,
+            _rng: &mut dyn RngCore,
             _room: &FloorRoom,
         ) -> crate::room::room::DungeonRoom {
             create_horizontal_hallway()
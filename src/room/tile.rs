@@ -1,8 +1,50 @@
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DungeonTile {
     Floor,
     Wall,
     Exit,
     StairsUp,
     StairsDown,
+    Door,
+    /// A door which needs a key to be opened
+    LockedDoor,
+    /// A door looking like a wall until it is found
+    SecretDoor,
+    Key,
+    Water,
+    Lava,
+    Chasm,
+    /// A walkable crossing over water, lava or a chasm
+    Bridge,
+}
+
+impl DungeonTile {
+    /// Whether the tile can be walked on; every tile besides walls and hazards is walkable, including doors
+    pub fn is_walkable(&self) -> bool {
+        *self != DungeonTile::Wall && !self.is_hazard()
+    }
+
+    pub fn is_door(&self) -> bool {
+        matches!(
+            self,
+            DungeonTile::Door | DungeonTile::LockedDoor | DungeonTile::SecretDoor
+        )
+    }
+
+    pub fn is_hazard(&self) -> bool {
+        matches!(
+            self,
+            DungeonTile::Water | DungeonTile::Lava | DungeonTile::Chasm
+        )
+    }
+}
+
+/// A rectangular grid of tiles, stored row by row
+pub trait TileGrid {
+    fn grid_tiles(&self) -> &[DungeonTile];
+
+    fn grid_rows(&self) -> usize;
+
+    fn grid_columns(&self) -> usize;
 }
@@ -0,0 +1,76 @@
+/// Index of a tile after turning a grid with the given dimensions by 90 degrees clockwise.
+/// The turned grid has swapped dimensions: it has as many rows as the original grid has columns.
+pub fn rotate_idx(rows: usize, cols: usize, idx: usize) -> usize {
+    let row = idx / cols;
+    let col = idx % cols;
+    col * rows + (rows - 1 - row)
+}
+
+/// Index of a tile after mirroring a grid along its vertical axis
+pub fn mirror_idx(cols: usize, idx: usize) -> usize {
+    let row = idx / cols;
+    let col = idx % cols;
+    row * cols + (cols - 1 - col)
+}
+
+/// Turns the tiles of a grid by 90 degrees clockwise
+pub fn rotate_tiles<T: Clone>(rows: usize, cols: usize, tiles: &[T]) -> Vec<T> {
+    let mut result = tiles.to_vec();
+    for (idx, tile) in tiles.iter().enumerate() {
+        result[rotate_idx(rows, cols, idx)] = tile.clone();
+    }
+
+    result
+}
+
+/// Mirrors the tiles of a grid along its vertical axis, which swaps left and right
+pub fn mirror_tiles<T: Clone>(cols: usize, tiles: &[T]) -> Vec<T> {
+    let mut result = tiles.to_vec();
+    for (idx, tile) in tiles.iter().enumerate() {
+        result[mirror_idx(cols, idx)] = tile.clone();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotates_tiles_clockwise() {
+        // 1 2 3      4 1
+        // 4 5 6  =>  5 2
+        //            6 3
+        let result = rotate_tiles(2, 3, &[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(vec![4, 1, 5, 2, 6, 3], result);
+    }
+
+    #[test]
+    fn rotating_four_times_restores_tiles() {
+        let tiles: Vec<usize> = (0..12).collect();
+
+        let once = rotate_tiles(3, 4, &tiles);
+        let twice = rotate_tiles(4, 3, &once);
+        let thrice = rotate_tiles(3, 4, &twice);
+        let result = rotate_tiles(4, 3, &thrice);
+
+        assert_eq!(tiles, result);
+    }
+
+    #[test]
+    fn mirrors_tiles_horizontally() {
+        let result = mirror_tiles(3, &[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(vec![3, 2, 1, 6, 5, 4], result);
+    }
+
+    #[test]
+    fn calculates_rotated_and_mirrored_index() {
+        assert_eq!(1, rotate_idx(2, 3, 0));
+        assert_eq!(4, rotate_idx(2, 3, 5));
+        assert_eq!(2, mirror_idx(3, 0));
+        assert_eq!(4, mirror_idx(3, 4));
+    }
+}
@@ -0,0 +1,442 @@
+use std::cmp::{max, min};
+
+use rand::prelude::*;
+
+use crate::{direction::Direction3D, error::GenerationError, floor::floor_architecture::FloorRoom};
+
+use super::{
+    math::UPosition, print::parse_room, room::DungeonRoom, room_builder::RoomBuilder,
+    tile::DungeonTile,
+};
+
+const DIRECTIONS: [Direction3D; 4] = [
+    Direction3D::Top,
+    Direction3D::Bottom,
+    Direction3D::Left,
+    Direction3D::Right,
+];
+
+/// Adjacency rules learned from a sample room.
+/// Every tile type of the sample gets an index into the palette; options of a cell are stored as a bit mask over these indexes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WfcRules {
+    palette: Vec<DungeonTile>,
+    weights: Vec<usize>,
+    // allowed[direction][tile] is the mask of tiles that may be placed next to tile in that direction
+    allowed: [Vec<u64>; 4],
+}
+
+impl WfcRules {
+    /// Learns which tiles may neighbor each other from a sample in the format of print_room.
+    /// Exits and stairs are placed by the room builder, so in the sample they count as floor tiles.
+    /// Returns None if the sample can not be parsed.
+    pub fn learn(sample: &str) -> Option<Self> {
+        let room = parse_room(sample)?;
+        let tiles: Vec<DungeonTile> = room.tiles.iter().map(normalize).collect();
+
+        let mut palette: Vec<DungeonTile> = vec![];
+        let mut weights = vec![];
+        for tile in tiles.iter() {
+            match palette.iter().position(|t| t == tile) {
+                Some(idx) => weights[idx] += 1,
+                None => {
+                    palette.push(*tile);
+                    weights.push(1);
+                }
+            }
+        }
+
+        let mut allowed = [
+            vec![0; palette.len()],
+            vec![0; palette.len()],
+            vec![0; palette.len()],
+            vec![0; palette.len()],
+        ];
+        for (idx, tile) in tiles.iter().enumerate() {
+            let tile_idx = palette.iter().position(|t| t == tile)?;
+            for (dir_idx, direction) in DIRECTIONS.iter().enumerate() {
+                if let Some(neighbor) = neighbor(room.rows, room.columns, idx, *direction) {
+                    let neighbor_idx = palette.iter().position(|t| *t == tiles[neighbor])?;
+                    allowed[dir_idx][tile_idx] |= 1 << neighbor_idx;
+                }
+            }
+        }
+
+        Some(Self {
+            palette,
+            weights,
+            allowed,
+        })
+    }
+
+    fn all(&self) -> u64 {
+        (1 << self.palette.len()) - 1
+    }
+
+    fn mask_of(&self, tile: DungeonTile) -> u64 {
+        match self.palette.iter().position(|t| *t == tile) {
+            Some(idx) => 1 << idx,
+            None => 0,
+        }
+    }
+
+    // Union of all tiles allowed next to any of the options in the given direction
+    fn allowed_next_to(&self, options: u64, dir_idx: usize) -> u64 {
+        (0..self.palette.len())
+            .filter(|t| options & (1 << t) != 0)
+            .fold(0, |acc, t| acc | self.allowed[dir_idx][t])
+    }
+}
+
+/// Builds rooms with the wave function collapse algorithm.
+/// The local style of the room follows the adjacency rules learned from a sample, see WfcRules::learn.
+///
+/// The border of the room is always made of walls.
+/// If a run ends in a contradiction, it restarts, until max_attempts is reached.
+/// If all attempts fail, an open room surrounded by walls is created instead.
+#[derive(Clone, Debug)]
+pub struct WfcRoomBuilder {
+    pub rows: usize,
+    pub cols: usize,
+    pub max_attempts: u8,
+    pub rules: WfcRules,
+}
+
+impl WfcRoomBuilder {
+    /// Create a builder from a sample room; returns None if the sample can not be parsed
+    pub fn new(rows: usize, cols: usize, sample: &str) -> Option<Self> {
+        Some(Self {
+            rows,
+            cols,
+            max_attempts: 10,
+            rules: WfcRules::learn(sample)?,
+        })
+    }
+}
+
+impl RoomBuilder for WfcRoomBuilder {
+    fn create_room(&self, rng: &mut dyn RngCore, room_config: &FloorRoom) -> DungeonRoom {
+        self.try_create_room(rng, room_config)
+            .expect("unable to create wfc room")
+    }
+
+    fn try_create_room(
+        &self,
+        rng: &mut dyn RngCore,
+        room_config: &FloorRoom,
+    ) -> Result<DungeonRoom, GenerationError> {
+        if self.rows < 3 || self.cols < 3 {
+            return Err(GenerationError::InvalidConfig(
+                "wfc rooms need at least 3 rows and columns".to_string(),
+            ));
+        }
+
+        let tiles = (0..self.max_attempts)
+            .find_map(|_| self.collapse(rng))
+            .unwrap_or_else(|| self.open_room());
+
+        let mut room = DungeonRoom {
+            tiles,
+            rows: self.rows,
+            columns: self.cols,
+            stair_up: room_config.stair_up,
+            stair_down: room_config.stair_down,
+            ..Default::default()
+        };
+
+        room.pathing();
+        self.set_exits(&mut room, &room_config.exits);
+        room.pathing();
+
+        Ok(room)
+    }
+
+    fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    fn get_cols(&self) -> usize {
+        self.cols
+    }
+}
+
+impl WfcRoomBuilder {
+    // Runs a single attempt of the algorithm; returns None on a contradiction
+    fn collapse(&self, rng: &mut dyn RngCore) -> Option<Vec<DungeonTile>> {
+        let rules = &self.rules;
+        let mut cells = vec![rules.all(); self.rows * self.cols];
+
+        // restrict the border to walls and propagate the constraint into the room
+        let wall = rules.mask_of(DungeonTile::Wall);
+        let mut changed = vec![];
+        for (idx, cell) in cells.iter_mut().enumerate() {
+            let row = idx / self.cols;
+            let col = idx % self.cols;
+            if row == 0 || col == 0 || row == self.rows - 1 || col == self.cols - 1 {
+                *cell = wall;
+                changed.push(idx);
+            }
+        }
+        self.propagate(&mut cells, changed)?;
+
+        while let Some(idx) = self.lowest_entropy_cell(rng, &cells) {
+            let options: Vec<usize> = (0..rules.palette.len())
+                .filter(|t| cells[idx] & (1 << t) != 0)
+                .collect();
+            let chosen = options.choose_weighted(rng, |t| rules.weights[*t]).ok()?;
+            cells[idx] = 1 << chosen;
+            self.propagate(&mut cells, vec![idx])?;
+        }
+
+        let tiles: Vec<DungeonTile> = cells
+            .iter()
+            .map(|c| rules.palette[c.trailing_zeros() as usize])
+            .collect();
+
+        // a room without walkable tiles is as useless as a contradiction
+        if tiles.iter().all(|t| *t == DungeonTile::Wall) {
+            return None;
+        }
+
+        Some(tiles)
+    }
+
+    // Finds an undecided cell with the fewest options left; ties are broken randomly
+    fn lowest_entropy_cell(&self, rng: &mut dyn RngCore, cells: &[u64]) -> Option<usize> {
+        let mut lowest = u32::MAX;
+        let mut candidates = vec![];
+        for (idx, cell) in cells.iter().enumerate() {
+            let options = cell.count_ones();
+            if options < 2 || options > lowest {
+                continue;
+            }
+
+            if options < lowest {
+                lowest = options;
+                candidates.clear();
+            }
+            candidates.push(idx);
+        }
+
+        candidates.choose(rng).copied()
+    }
+
+    // Removes options from neighboring cells until all cells agree with the rules again.
+    // Returns None if a cell runs out of options.
+    fn propagate(&self, cells: &mut [u64], mut changed: Vec<usize>) -> Option<()> {
+        while let Some(idx) = changed.pop() {
+            if cells[idx] == 0 {
+                return None;
+            }
+
+            for (dir_idx, direction) in DIRECTIONS.iter().enumerate() {
+                if let Some(n) = neighbor(self.rows, self.cols, idx, *direction) {
+                    let reduced = cells[n] & self.rules.allowed_next_to(cells[idx], dir_idx);
+                    if reduced != cells[n] {
+                        cells[n] = reduced;
+                        changed.push(n);
+                    }
+                }
+            }
+        }
+
+        Some(())
+    }
+
+    fn open_room(&self) -> Vec<DungeonTile> {
+        (0..self.rows * self.cols)
+            .map(|idx| {
+                let row = idx / self.cols;
+                let col = idx % self.cols;
+                if row == 0 || col == 0 || row == self.rows - 1 || col == self.cols - 1 {
+                    DungeonTile::Wall
+                } else {
+                    DungeonTile::Floor
+                }
+            })
+            .collect()
+    }
+
+    // Places an exit at the center of each requested side and digs a corridor to the closest walkable tile
+    fn set_exits(&self, room: &mut DungeonRoom, exits: &[Direction3D]) {
+        for direction in exits {
+            let exit = match direction {
+                Direction3D::Top => UPosition::new(0, self.cols / 2),
+                Direction3D::Bottom => UPosition::new(self.rows - 1, self.cols / 2),
+                Direction3D::Left => UPosition::new(self.rows / 2, 0),
+                Direction3D::Right => UPosition::new(self.rows / 2, self.cols - 1),
+                _ => continue,
+            };
+
+            let inner = match direction {
+                Direction3D::Top => UPosition::new(1, exit.col),
+                Direction3D::Bottom => UPosition::new(exit.row - 1, exit.col),
+                Direction3D::Left => UPosition::new(exit.row, 1),
+                _ => UPosition::new(exit.row, exit.col - 1),
+            };
+            let target = room
+                .pathing
+                .iter()
+                .map(|idx| UPosition::new(room.row(*idx), room.col(*idx)))
+                .min_by_key(|p| p.row.abs_diff(inner.row) + p.col.abs_diff(inner.col))
+                .unwrap_or(inner);
+
+            match direction {
+                Direction3D::Top | Direction3D::Bottom => {
+                    self.dig(room, inner.row, target.row, inner.col, true);
+                    self.dig(room, inner.col, target.col, target.row, false);
+                }
+                _ => {
+                    self.dig(room, inner.col, target.col, inner.row, false);
+                    self.dig(room, inner.row, target.row, target.col, true);
+                }
+            }
+
+            let idx = room.room_idx(exit.row, exit.col);
+            room.tiles[idx] = DungeonTile::Exit;
+        }
+    }
+
+    fn dig(&self, room: &mut DungeonRoom, from: usize, to: usize, fixed: usize, vertical: bool) {
+        for i in min(from, to)..=max(from, to) {
+            let idx = if vertical {
+                room.room_idx(i, fixed)
+            } else {
+                room.room_idx(fixed, i)
+            };
+
+            if room.tiles[idx] == DungeonTile::Wall {
+                room.tiles[idx] = DungeonTile::Floor;
+            }
+        }
+    }
+}
+
+fn normalize(tile: &DungeonTile) -> DungeonTile {
+    match tile {
+        DungeonTile::Exit | DungeonTile::StairsUp | DungeonTile::StairsDown => DungeonTile::Floor,
+        tile if tile.is_door() => DungeonTile::Floor,
+        _ => *tile,
+    }
+}
+
+fn neighbor(rows: usize, cols: usize, idx: usize, direction: Direction3D) -> Option<usize> {
+    let row = idx / cols;
+    let col = idx % cols;
+    match direction {
+        Direction3D::Top if row > 0 => Some(idx - cols),
+        Direction3D::Bottom if row < rows - 1 => Some(idx + cols),
+        Direction3D::Left if col > 0 => Some(idx - 1),
+        Direction3D::Right if col < cols - 1 => Some(idx + 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::room::print::print_room;
+
+    use rand_pcg::Pcg64;
+
+    use super::*;
+
+    const SAMPLE: &str = "#########
+#...#...#
+#.#...#.#
+#...#...#
+##.###.##
+#...#...#
+#.#...#.#
+#...#...#
+#########";
+
+    #[test]
+    fn learns_adjacency_rules_from_sample() {
+        let result = WfcRules::learn("###\n#.#\n#E#").unwrap();
+
+        assert_eq!(vec![DungeonTile::Wall, DungeonTile::Floor], result.palette);
+        assert_eq!(vec![7, 2], result.weights);
+        // floors only ever have walls to their left and right
+        assert_eq!(0b01, result.allowed[2][1]);
+        assert_eq!(0b01, result.allowed[3][1]);
+        // above floors there are walls or floors, but below them only floors
+        assert_eq!(0b11, result.allowed[0][1]);
+        assert_eq!(0b10, result.allowed[1][1]);
+    }
+
+    #[test]
+    fn does_not_learn_from_unparsable_sample() {
+        assert_eq!(None, WfcRules::learn("#x#"));
+    }
+
+    #[test]
+    fn creates_printable_room() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = WfcRoomBuilder::new(12, 12, SAMPLE).unwrap();
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Top, Direction3D::Right],
+            ..Default::default()
+        };
+
+        let room = sut.create_room(&mut rng, &room_config);
+
+        let expected_tiles = "######E#####
+##.###.#####
+#.####....##
+#..####....#
+##...#####.#
+#.#.#####..#
+###.#.#....E
+##.####..###
+#.#.#...#..#
+##...#...###
+#.###..###.#
+############"
+            .to_string();
+        let room_tile_str = print_room(room.rows, room.columns, room.tiles, 0, 0);
+        assert_eq!(expected_tiles, room_tile_str);
+    }
+
+    #[test]
+    fn connects_requested_exits() {
+        let sut = WfcRoomBuilder::new(20, 24, SAMPLE).unwrap();
+        let room_config = FloorRoom {
+            exits: vec![Direction3D::Bottom, Direction3D::Left, Direction3D::Top],
+            ..Default::default()
+        };
+
+        for seed in 0..10 {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let room = sut.create_room(&mut rng, &room_config);
+
+            assert_eq!(3, room.exit_directions.len());
+            for exit in room.exits.iter() {
+                assert_eq!(DungeonTile::Exit, room.tiles[*exit]);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_too_small_rooms() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let sut = WfcRoomBuilder::new(2, 12, SAMPLE).unwrap();
+
+        let result = sut.try_create_room(&mut rng, &FloorRoom::default());
+
+        assert!(matches!(result, Err(GenerationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn falls_back_to_open_room_on_contradictions() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        // walls are never next to walls in this sample, so the wall border can not be satisfied
+        let sut = WfcRoomBuilder::new(6, 6, "#.\n.#").unwrap();
+
+        let room = sut.create_room(&mut rng, &FloorRoom::default());
+
+        assert_eq!(
+            "######\n#....#\n#....#\n#....#\n#....#\n######",
+            print_room(room.rows, room.columns, room.tiles, 0, 0)
+        );
+    }
+}